@@ -1,30 +1,21 @@
 use day02::intcode;
+use day02::solver::{self, GRAVITY_ASSIST_TARGET};
 
 fn main() {
     let input = intcode::input::input_op_codes();
 
-    'TOP: for noun in 0..100 {
-        for verb in 0..100 {
-            let mut op_codes = input.clone();
-            let op_codes = op_codes.as_mut_slice();
-
-            intcode::replace_at_pos(op_codes, 1, noun);
-            intcode::replace_at_pos(op_codes, 2, verb);
-            intcode::process_op_codes(op_codes);
-
-            let output = op_codes[0];
-            let op_codes = op_codes.iter().map(ToString::to_string).collect::<Vec<String>>().join(",");
-
-            println!("output op codes: {}", op_codes);
-            println!("program output (@0): {}", output);
+    match solver::linear_output(&input) {
+        Some(output) => println!("program output (@0) = {} + {} * noun + {} * verb", output.constant, output.noun, output.verb),
+        None => println!("program output (@0) is not linear in noun and verb; searching")
+    }
 
-            if output == 19_690_720 {
-                let predicate = 100 * noun + verb;
+    let solutions = solver::solve(&input, GRAVITY_ASSIST_TARGET, 0..100, 0..100);
 
-                println!("found predicate: 100 * {} + {} = {}", noun, verb, predicate);
+    for solution in &solutions {
+        println!("found predicate: 100 * {} + {} = {}", solution.noun, solution.verb, solution.predicate());
+    }
 
-                break 'TOP;
-            }
-        }
+    if solutions.is_empty() {
+        println!("no noun and verb produce {}", GRAVITY_ASSIST_TARGET);
     }
 }
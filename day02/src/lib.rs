@@ -1,109 +1,51 @@
 pub mod intcode {
     pub mod input {
-        use advent_of_code_2019::input;
-
         pub fn input_op_codes() -> Vec<i64> {
-            let input = input::single_line_from_arg_file();
-
-            println!("input op codes: {}", input);
+            let op_codes = ::intcode::input::program_from_arg_file();
 
-            let mut op_codes = Vec::new();
-            for op_code in input.split(',') {
-                op_codes.push(op_code.parse::<i64>().unwrap());
-            }
+            println!("input op codes: {}", op_codes.iter().map(ToString::to_string).collect::<Vec<String>>().join(","));
 
             op_codes
         }
     }
 
-    use std::fmt::{Formatter, Error};
-
-    #[derive(Debug)]
-    pub enum OpCode {
-        ADD,
-        MULTIPLY,
-        TERMINATE
-    }
+    use ::intcode::Machine;
 
-    impl std::fmt::Display for OpCode {
-        fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
-            write!(f, "{:?}", self)
-        }
+    pub fn replace_at_pos(codes: &mut [i64], pos: usize, code: i64) {
+        codes[pos] = code;
     }
 
-    const INT_VALUE_ADD: i64 = 1;
-    const INT_VALUE_MULTIPLY: i64 = 2;
-    const INT_VALUE_TERMINATE: i64 = 99;
-
-    impl OpCode {
-        fn of_int(op_code: i64) -> Option<OpCode> {
-            match op_code {
-                INT_VALUE_ADD => Some(OpCode::ADD),
-                INT_VALUE_MULTIPLY => Some(OpCode::MULTIPLY),
-                INT_VALUE_TERMINATE => Some(OpCode::TERMINATE),
-                _ => None
-            }
-        }
-
-        fn operation(&self) -> Option<&dyn Fn(i64, i64) -> i64> {
-            match self {
-                OpCode::ADD => Some(&|left, right| left + right),
-                OpCode::MULTIPLY => Some(&|left, right| left * right),
-                OpCode::TERMINATE => None
-            }
-        }
+    pub fn process_op_codes(codes: &mut [i64]) {
+        let mut machine = Machine::new(codes);
 
-        fn process_at_pos(codes: &mut [i64], idx: usize) -> Option<usize> {
-            Option::and_then(OpCode::of_int(codes[idx]), &mut |op_code: OpCode| op_code.process(codes, idx))
+        if let Err(err) = machine.run() {
+            panic!("{}", err);
         }
 
-        fn process(&self, codes: &mut [i64], idx: usize) -> Option<usize> {
-            //print!("processing: @{}: {}", idx, self.to_string());
-
-            let idx = Option::map(self.operation(), |op| {
-                if codes.len() < idx + 3 {
-                    panic!("opcode array does not contain expected opcode operands: {}", self.to_string());
-                }
-
-                let left_src = codes[idx + 1];
-                let right_src = codes[idx + 2];
-                let dest = codes[idx + 3];
-                let left = codes[left_src as usize];
-                let right = codes[right_src as usize];
-                let result = op(left, right);
-
-                //println!(": (@{}, @{} -> @{}) => {}, {} -> {}", left_src, right_src, dest, left, right, result);
-
-                replace_at_pos(codes, dest as usize, result);
-
-                idx + 4
-            });
-
-            //if idx.is_none() { println!() }
-
-            idx
-        }
+        codes.copy_from_slice(&machine.memory()[..codes.len()]);
     }
 
-    pub fn replace_at_pos(codes: &mut [i64], pos: usize, code: i64) {
-        //println!("replacing: @{}: {}", pos, code);
+    /// Runs a fork of the given machine with the noun and verb written to addresses 1 and 2, returning the value left
+    /// at address 0.
+    pub fn output_for_noun_and_verb(initial: &Machine, noun: i64, verb: i64) -> i64 {
+        let mut machine = initial.clone();
 
-        codes[pos] = code;
-    }
+        machine.write(1, noun);
+        machine.write(2, verb);
 
-    pub fn process_op_codes(codes: &mut [i64]) {
-        let mut idx = 0;
-        while idx < codes.len() {
-            match OpCode::process_at_pos(codes, idx) {
-                Some(new_idx) => idx = new_idx,
-                None => break
-            }
+        if let Err(err) = machine.run() {
+            panic!("{}", err);
         }
+
+        machine.read(0)
     }
 
     #[cfg(test)]
+    #[allow(clippy::unnecessary_mut_passed, clippy::legacy_numeric_constants)]
     mod tests {
-        use crate::intcode::replace_at_pos;
+        use crate::intcode::{output_for_noun_and_verb, replace_at_pos};
+        use ::intcode::fixture;
+        use ::intcode::Machine;
 
         fn test_replace_at(op_codes: &mut [i64], idx: usize, value: i64, expected: &[i64]) {
             replace_at_pos(op_codes, idx, value);
@@ -223,5 +165,260 @@ pub mod intcode {
         fn supplied_test_case_4() {
             test_process_op_codes(&mut [1,1,1,4,99,5,6,0,99], &mut [30,1,1,4,2,5,6,0,99]);
         }
+
+        #[test]
+        fn supplied_fixtures_pass() {
+            let report = fixture::run_directory(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures")).unwrap();
+
+            assert!(report.all_passed(), "{}", report);
+            assert_eq!(report.num_passed(), 5);
+        }
+
+        #[test]
+        fn forks_share_initial_state() {
+            let initial = Machine::new(&[1, 0, 0, 0, 99, 7, 8]);
+
+            assert_eq!(output_for_noun_and_verb(&initial, 5, 6), 15);
+            assert_eq!(output_for_noun_and_verb(&initial, 4, 5), 106);
+            assert_eq!(initial.memory(), &[1, 0, 0, 0, 99, 7, 8]);
+        }
+    }
+}
+
+pub mod solver {
+    use intcode::symbolic::{Expr, Path, PathEnd, SymbolicExecutor};
+    use intcode::Machine;
+    use std::ops::Range;
+    use std::thread;
+
+    pub const GRAVITY_ASSIST_TARGET: i64 = 19_690_720;
+
+    const NOUN_ADDRESS: usize = 1;
+    const VERB_ADDRESS: usize = 2;
+    const NOUN: &str = "noun";
+    const VERB: &str = "verb";
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Solution {
+        pub noun: i64,
+        pub verb: i64
+    }
+
+    impl Solution {
+        pub fn predicate(&self) -> i64 {
+            100 * self.noun + self.verb
+        }
+    }
+
+    /// Value of the form `constant + noun * noun_coefficient + verb * verb_coefficient`.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct Linear {
+        pub constant: i64,
+        pub noun: i64,
+        pub verb: i64
+    }
+
+    impl Linear {
+        fn constant(value: i64) -> Linear {
+            Linear { constant: value, noun: 0, verb: 0 }
+        }
+
+        fn as_constant(&self) -> Option<i64> {
+            if self.noun == 0 && self.verb == 0 { Some(self.constant) } else { None }
+        }
+
+        /// Sum of two values, or `None` if a term overflows.
+        fn add(&self, other: &Linear) -> Option<Linear> {
+            Some(Linear {
+                constant: self.constant.checked_add(other.constant)?,
+                noun: self.noun.checked_add(other.noun)?,
+                verb: self.verb.checked_add(other.verb)?
+            })
+        }
+
+        /// Value multiplied by a constant, or `None` if a term overflows.
+        fn scale(&self, factor: i64) -> Option<Linear> {
+            Some(Linear {
+                constant: self.constant.checked_mul(factor)?,
+                noun: self.noun.checked_mul(factor)?,
+                verb: self.verb.checked_mul(factor)?
+            })
+        }
+
+        /// Product of two values, or `None` if it is not linear or a term overflows.
+        fn multiply(&self, other: &Linear) -> Option<Linear> {
+            match (self.as_constant(), other.as_constant()) {
+                (Some(factor), _) => other.scale(factor),
+                (_, Some(factor)) => self.scale(factor),
+                (None, None) => None
+            }
+        }
+    }
+
+    /// Runs the output for a single noun and verb, or `None` if the program faults.
+    fn output_for(initial: &Machine, noun: i64, verb: i64) -> Option<i64> {
+        let mut machine = initial.clone();
+
+        machine.write(NOUN_ADDRESS, noun);
+        machine.write(VERB_ADDRESS, verb);
+        machine.run().ok()?;
+
+        Some(machine.read(0))
+    }
+
+    /// Tries every noun and verb in the given ranges, splitting the nouns between `num_threads` threads. Pairs for which
+    /// the program faults are not solutions.
+    pub fn solve_parallel(program: &[i64], target: i64, nouns: Range<i64>, verbs: Range<i64>, num_threads: usize) -> Vec<Solution> {
+        let nouns = nouns.collect::<Vec<i64>>();
+        let chunk_len = nouns.len().div_ceil(num_threads.max(1)).max(1);
+
+        thread::scope(|scope| {
+            let workers = nouns.chunks(chunk_len).map(|chunk| {
+                let verbs = verbs.clone();
+
+                scope.spawn(move || {
+                    let initial = Machine::new(program);
+
+                    chunk.iter()
+                        .flat_map(|noun| verbs.clone().map(move |verb| Solution { noun: *noun, verb }))
+                        .filter(|solution| output_for(&initial, solution.noun, solution.verb) == Some(target))
+                        .collect::<Vec<Solution>>()
+                })
+            }).collect::<Vec<_>>();
+
+            workers.into_iter().flat_map(|worker| worker.join().unwrap()).collect()
+        })
+    }
+
+    fn linear(expr: &Expr) -> Option<Linear> {
+        match expr {
+            Expr::Const(value) => Some(Linear::constant(*value)),
+            Expr::Var(name) if name == NOUN => Some(Linear { constant: 0, noun: 1, verb: 0 }),
+            Expr::Var(name) if name == VERB => Some(Linear { constant: 0, noun: 0, verb: 1 }),
+            Expr::Add(left, right) => linear(left)?.add(&linear(right)?),
+            Expr::Mul(left, right) => linear(left)?.multiply(&linear(right)?),
+            _ => None
+        }
+    }
+
+    /// Executes the program symbolically with the noun and verb as variables, returning the value left at address 0
+    /// if it is linear in them. Output depending on a value read through an address computed from the noun or verb,
+    /// or on any branch, is not linear.
+    pub fn linear_output(program: &[i64]) -> Option<Linear> {
+        if program.len() <= VERB_ADDRESS {
+            return None;
+        }
+
+        let mut executor = SymbolicExecutor::new(program);
+
+        executor.set_symbolic(NOUN_ADDRESS, NOUN);
+        executor.set_symbolic(VERB_ADDRESS, VERB);
+
+        match executor.explore().as_slice() {
+            [Path { end: PathEnd::Halted, memory, .. }] => linear(memory.first()?),
+            _ => None
+        }
+    }
+
+    /// Solves for every noun and verb in the given ranges directly, or returns `None` if the program's output is not
+    /// linear in them or solving overflows. The machine faults on overflow, so each solution found is confirmed by
+    /// running the program, in case it overflows computing a value whose linear form does not.
+    pub fn solve_analytic(program: &[i64], target: i64, nouns: Range<i64>, verbs: Range<i64>) -> Option<Vec<Solution>> {
+        let output = linear_output(program)?;
+        let mut solutions = Vec::new();
+
+        for noun in nouns {
+            let remainder = target.checked_sub(output.constant)?.checked_sub(output.noun.checked_mul(noun)?)?;
+
+            if output.verb == 0 {
+                if remainder == 0 {
+                    solutions.extend(verbs.clone().map(|verb| Solution { noun, verb }));
+                }
+            } else if remainder % output.verb == 0 && verbs.contains(&(remainder / output.verb)) {
+                solutions.push(Solution { noun, verb: remainder / output.verb });
+            }
+        }
+
+        let initial = Machine::new(program);
+
+        solutions.retain(|solution| output_for(&initial, solution.noun, solution.verb) == Some(target));
+
+        Some(solutions)
+    }
+
+    /// Finds every noun and verb in the given ranges for which the program outputs the target, analytically where
+    /// possible and otherwise by searching in parallel. Solutions are ordered by noun, then verb.
+    pub fn solve(program: &[i64], target: i64, nouns: Range<i64>, verbs: Range<i64>) -> Vec<Solution> {
+        solve_analytic(program, target, nouns.clone(), verbs.clone()).unwrap_or_else(|| {
+            let num_threads = thread::available_parallelism().map_or(1, |num_threads| num_threads.get());
+
+            solve_parallel(program, target, nouns, verbs, num_threads)
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::solver::{linear_output, solve, solve_analytic, solve_parallel, Linear, Solution};
+
+        // Computes 2 * noun + verb + 4 into address 0, after first adding the values addressed by noun and verb.
+        const LINEAR: [i64; 18] = [1, 0, 0, 3, 1, 1, 2, 3, 1, 3, 1, 3, 1, 3, 17, 0, 99, 4];
+        // Computes noun * verb into address 0.
+        const PRODUCT: [i64; 9] = [1, 0, 0, 3, 2, 1, 2, 0, 99];
+
+        #[test]
+        fn linear_output_of_linear_program() {
+            assert_eq!(linear_output(&LINEAR), Some(Linear { constant: 4, noun: 2, verb: 1 }));
+        }
+
+        #[test]
+        fn product_is_not_linear() {
+            assert_eq!(linear_output(&PRODUCT), None);
+            assert_eq!(linear_output(&[1, 0, 0, 0, 99]), None);
+        }
+
+        #[test]
+        fn analytic_and_parallel_agree() {
+            let analytic = solve_analytic(&LINEAR, 30, 0..20, 0..20).unwrap();
+
+            assert_eq!(analytic.len(), 10);
+            assert_eq!(analytic[0], Solution { noun: 4, verb: 18 });
+            assert_eq!(solve_parallel(&LINEAR, 30, 0..20, 0..20, 3), analytic);
+        }
+
+        #[test]
+        fn overflowing_solve_searched() {
+            // Computes (noun + verb) * K, overflowing when noun + verb exceeds 50.
+            const K: i64 = i64::MAX / 50;
+            let program = [1101, 0, 0, 9, 1002, 9, K, 0, 99];
+
+            assert_eq!(linear_output(&program), Some(Linear { constant: 0, noun: K, verb: K }));
+            assert_eq!(solve_analytic(&program, 10 * K, 0..100, 0..100), None);
+            assert_eq!(solve(&program, 10 * K, 0..100, 0..100).len(), 11);
+        }
+
+        #[test]
+        fn solutions_overflowing_in_machine_rejected() {
+            // Computes noun + verb, via ((noun + verb) * K) + ((noun + verb) * -K), which overflows when noun + verb
+            // exceeds 4.
+            const K: i64 = i64::MAX / 5;
+            let program = [1101, 0, 0, 30, 1002, 30, K, 31, 1002, 30, -K, 32, 1, 31, 32, 33, 1, 33, 30, 0, 99];
+
+            assert_eq!(linear_output(&program), Some(Linear { constant: 0, noun: 1, verb: 1 }));
+            assert_eq!(solve_analytic(&program, 4, 0..10, 0..10).unwrap().len(), 5);
+            assert_eq!(solve_analytic(&program, 10, 0..10, 0..10), Some(vec![]));
+            assert_eq!(solve_parallel(&program, 10, 0..10, 0..10, 2), vec![]);
+        }
+
+        #[test]
+        fn non_linear_program_searched() {
+            assert_eq!(solve(&PRODUCT, 4, 0..5, 0..5), vec![
+                Solution { noun: 1, verb: 4 }, Solution { noun: 2, verb: 2 }, Solution { noun: 4, verb: 1 }
+            ]);
+        }
+
+        #[test]
+        fn predicate() {
+            assert_eq!(Solution { noun: 12, verb: 2 }.predicate(), 1202);
+        }
     }
 }
@@ -1,3 +1,17 @@
+use day05::diagnostic;
+use day05::intcode;
+
 fn main() {
-    println!("ran");
-}
\ No newline at end of file
+    let program = intcode::input::program_from_arg_file();
+    let outputs = match diagnostic::run_diagnostic(&program, diagnostic::AIR_CONDITIONER_SYSTEM_ID) {
+        Ok(outputs) => outputs,
+        Err(err) => panic!("diagnostic program faulted: {}", err)
+    };
+
+    println!("diagnostic outputs: {:?}", outputs);
+
+    match diagnostic::diagnostic_code(&outputs) {
+        Ok(code) => println!("diagnostic code: {}", code),
+        Err(err) => println!("diagnostic failed: {}", err)
+    }
+}
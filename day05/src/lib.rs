@@ -1,240 +1,119 @@
-pub mod intcode {
-    mod optype {
-        #[derive(Clone)]
-        pub(crate) enum ParameterMode {
-            POSITION,
-            IMMEDIATE
-        }
+pub use intcode;
 
-        impl ParameterMode {
-            pub fn parse(mode: i32) -> Result<ParameterMode, String> {
-                match mode {
-                    0 => Ok(ParameterMode::POSITION),
-                    1 => Ok(ParameterMode::IMMEDIATE),
-                    _ => Err(format!("invalid parameter mode: {}", mode))
-                }
-            }
-        }
+pub mod diagnostic {
+    use intcode::{IntcodeError, Machine};
 
-        pub(crate) struct ParameterLayout {
-            pub num_read: usize,
-            pub has_write: bool
-        }
+    pub const AIR_CONDITIONER_SYSTEM_ID: i64 = 1;
+    pub const THERMAL_RADIATOR_SYSTEM_ID: i64 = 5;
 
-        pub(crate) enum OpType {
-            ADD,
-            MULTIPLY,
-            INPUT,
-            OUTPUT,
-            TERMINATE
-        }
+    pub fn run_diagnostic(program: &[i64], system_id: i64) -> Result<Vec<i64>, IntcodeError> {
+        let mut machine = Machine::new(program);
 
-        impl OpType {
-            pub fn parse(op_code: i32) -> Result<OpType, String> {
-                match op_code {
-                    1 => Ok(OpType::ADD),
-                    2 => Ok(OpType::MULTIPLY),
-                    3 => Ok(OpType::INPUT),
-                    4 => Ok(OpType::OUTPUT),
-                    99 => Ok(OpType::TERMINATE),
-                    _ => Err(format!("invalid op code identifier: {}", op_code))
-                }
-            }
+        machine.enable_loop_detection();
+        machine.push_input(system_id);
+        machine.run()?;
 
-            pub(crate) fn parameter_layout(&self) -> &ParameterLayout {
-                match self {
-                    OpType::ADD | OpType::MULTIPLY => &ParameterLayout { num_read: 2, has_write: true },
-                    OpType::INPUT => &ParameterLayout { num_read: 1, has_write: true },
-                    OpType::OUTPUT | OpType::TERMINATE => &ParameterLayout { num_read: 0, has_write: false },
-                }
-            }
-
-            pub(crate) fn num_parameters(&self) -> usize {
-                let layout = self.parameter_layout();
-
-                return layout.num_read + if layout.has_write { 1 } else { 0 };
-            }
-        }
+        Ok(machine.outputs().to_vec())
     }
 
-    mod opcode {
-        use crate::intcode::optype::{OpType, ParameterMode};
-
-        pub(crate) struct OpCode {
-            pub op_type: OpType,
-            pub read_param_modes: Box<[ParameterMode]>
-        }
-
-        impl OpCode {
-            const DIGIT_BASE: i32 = 10;
-
-            fn reversed_digits(value: i32) -> Box<[i32]> {
-                let mut digits = Vec::new();
-
-                let mut remainder = value;
-                while value >= OpCode::DIGIT_BASE {
-                    digits.push(remainder % OpCode::DIGIT_BASE);
-
-                    remainder /= OpCode::DIGIT_BASE;
+    /// Returns the diagnostic code, which is the final output, provided every preceding test output was zero.
+    pub fn diagnostic_code(outputs: &[i64]) -> Result<i64, String> {
+        match outputs.split_last() {
+            Some((code, tests)) => {
+                if let Some(idx) = tests.iter().position(|output| *output != 0) {
+                    return Err(format!("diagnostic test {} failed with output: {}", idx, tests[idx]));
                 }
 
-                digits.push(remainder);
-
-                return digits.into_boxed_slice();
-            }
-
-            fn assemble_int_from_reversed_digits(digits: Box<[i32]>) -> i32 {
-                let mut op_code = 0;
-                for digit in digits.iter().rev() {
-                    op_code = OpCode::DIGIT_BASE * op_code + *digit;
-                }
-
-                return op_code;
-            }
-
-            fn parse_parameter_mode(digits: &Box<[i32]>, idx: usize) -> Result<ParameterMode, String> {
-                return ParameterMode::parse(digits[idx]).map_err(|err| format!("unable to parse parameter mode at index {}: {}", idx, err));
-            }
-
-            pub fn parse_parameter_modes(op_type: &OpType, digits: Box<[i32]>) -> Result<Box<[ParameterMode]>, String> {
-                let param_layout = op_type.parameter_layout();
-                let num_params_expected = op_type.num_parameters();
-                let num_params_found = digits.len();
-
-                if num_params_found != num_params_expected {
-                    return Err(format!("number of parameters specified in op code ({}) exceeds number expected ({})",
-                                       num_params_found, num_params_expected));
-                } else if param_layout.has_write {
-                    if let ParameterMode::IMMEDIATE = OpCode::parse_parameter_mode(&digits, num_params_found - 1)? {
-                        return Err("write parameter address mode specified in op code as immediate".to_string());
-                    }
-                }
-
-                let mut read_modes = Vec::with_capacity(param_layout.num_read);
-
-                for i in 0..num_params_found {
-                    read_modes[i] = OpCode::parse_parameter_mode(&digits, i)?;
-                }
-
-                read_modes.resize(param_layout.num_read as usize, ParameterMode::POSITION);
-
-                return Ok(read_modes.into_boxed_slice());
-            }
+                Ok(*code)
+            },
+            None => Err("diagnostic program produced no output".to_string())
+        }
+    }
 
-            pub fn parse(value: i32) -> Result<OpCode, String> {
-                let (op_code, param_modes) = {
-                    let digits = OpCode::reversed_digits(value);
-                    let num_digits = digits.len();
+    #[cfg(test)]
+    mod tests {
+        use crate::diagnostic::{run_diagnostic, diagnostic_code};
+        use intcode::IntcodeError;
 
-                    if num_digits < 1 {
-                        return Err(format!("unable to parse digits from op code"));
-                    }
+        const LARGER_EXAMPLE: [i64; 47] = [
+            3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,
+            1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,
+            999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99
+        ];
 
-                    let (op_code, param_modes) = digits.split_at(if num_digits > 1 { 2 } else { 1 });
+        fn test_outputs(program: &[i64], input: i64, expected: &[i64]) {
+            assert_eq!(run_diagnostic(program, input).unwrap(), expected);
+        }
 
-                    let op_code = op_code.to_vec().into_boxed_slice();
-                    let param_modes = param_modes.to_vec().into_boxed_slice();
+        #[test]
+        fn supplied_equal_to_8_position_mode() {
+            test_outputs(&[3,9,8,9,10,9,4,9,99,-1,8], 8, &[1]);
+            test_outputs(&[3,9,8,9,10,9,4,9,99,-1,8], 7, &[0]);
+        }
 
-                    (op_code, param_modes)
-                };
+        #[test]
+        fn supplied_less_than_8_position_mode() {
+            test_outputs(&[3,9,7,9,10,9,4,9,99,-1,8], 7, &[1]);
+            test_outputs(&[3,9,7,9,10,9,4,9,99,-1,8], 8, &[0]);
+        }
 
-                let op_type = OpType::parse(OpCode::assemble_int_from_reversed_digits(op_code))
-                    .map_err(|err| format!("unable to parse op code from value: {}: {}", value, err))?;
-                let read_param_modes = OpCode::parse_parameter_modes(&op_type, param_modes)
-                    .map_err(|err| format!("unable to parse parameter modes from op code: {}: {}", value, err))?;
+        #[test]
+        fn supplied_equal_to_8_immediate_mode() {
+            test_outputs(&[3,3,1108,-1,8,3,4,3,99], 8, &[1]);
+            test_outputs(&[3,3,1108,-1,8,3,4,3,99], 9, &[0]);
+        }
 
-                return Ok(OpCode { op_type, read_param_modes });
-            }
+        #[test]
+        fn supplied_less_than_8_immediate_mode() {
+            test_outputs(&[3,3,1107,-1,8,3,4,3,99], -3, &[1]);
+            test_outputs(&[3,3,1107,-1,8,3,4,3,99], 12, &[0]);
         }
-    }
 
-    mod processor {
-        use crate::intcode::{opcode::OpCode, optype::ParameterMode};
-        use crate::intcode::optype::OpType;
-        use std::io::stdin;
+        #[test]
+        fn supplied_jump_position_mode() {
+            test_outputs(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], 0, &[0]);
+            test_outputs(&[3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9], 5, &[1]);
+        }
 
-        fn resolve_read_parameters(param_modes: &[ParameterMode], program: &[i32], instruction_ptr: usize) -> Result<Box<[i32]>, String> {
-            let mut params = Vec::with_capacity(param_modes.len());
+        #[test]
+        fn supplied_jump_immediate_mode() {
+            test_outputs(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], 0, &[0]);
+            test_outputs(&[3,3,1105,-1,9,1101,0,0,12,4,12,99,1], -5, &[1]);
+        }
 
-            for i in 0 .. param_modes.len() {
-                let param_value = program[instruction_ptr + i];
+        #[test]
+        fn supplied_larger_example_below_8() {
+            test_outputs(&LARGER_EXAMPLE, 7, &[999]);
+        }
 
-                params[i] = match param_modes[i] {
-                    ParameterMode::POSITION => {
-                        if param_value < 0 || param_value as usize >= program.len() {
-                            return Err(format!("parameter index {} in position mode refers to out of range program address: {}", i, param_value));
-                        }
+        #[test]
+        fn supplied_larger_example_equal_to_8() {
+            test_outputs(&LARGER_EXAMPLE, 8, &[1000]);
+        }
 
-                        program[param_value as usize]
-                    },
-                    ParameterMode::IMMEDIATE => param_value
-                };
-            }
+        #[test]
+        fn supplied_larger_example_above_8() {
+            test_outputs(&LARGER_EXAMPLE, 9, &[1001]);
+        }
 
-            return Ok(params.into_boxed_slice());
+        #[test]
+        fn fault_reported_with_instruction_pointer() {
+            assert_eq!(run_diagnostic(&[3, 5, 1, 5, -1, 0, 99], 1).unwrap_err().instruction_ptr(), 2);
         }
 
-        fn process_instruction(op_code: &OpCode, params: &Box<[i32]>) -> Result<Option<i32>, String> {
-            match op_code.op_type {
-                OpType::ADD => Ok(Some(params[0] + params[1])),
-                OpType::MULTIPLY => Ok(Some(params[0] * params[1])),
-                OpType::INPUT => {
-                    let mut line = String::new();
-
-                    stdin().read_line(&mut line).map_err(|err| format!("unable to read from stdin: {}", err))?;
-
-                    line
-                        .trim_end()
-                        .parse::<i32>()
-                        .map_err(|err| err.to_string())
-                        .map(Option::Some)
-                },
-                OpType::OUTPUT => {
-                    println!("{}", params[0]);
-
-                    Ok(None)
-                }
-                OpType::TERMINATE => Ok(None)
-            }
+        #[test]
+        fn endless_loop_reported() {
+            assert!(matches!(run_diagnostic(&[3, 0, 1105, 1, 2], 1), Err(IntcodeError::InfiniteLoop { instruction_ptr: 2, .. })));
         }
 
-        pub fn process(program: &mut [i32]) {
-            let program_len = program.len();
-            let mut instruction_ptr = 0;
-            while instruction_ptr < program_len {
-                let op_code_value = program[instruction_ptr];
-
-                let op_code = match OpCode::parse(op_code_value) {
-                    Ok(op_code) => op_code,
-                    Err(err) => panic!("unable to parse op code: {}: {}", op_code_value, err)
-                };
-
-                let params = match resolve_read_parameters(&op_code.read_param_modes, program, instruction_ptr + 1) {
-                    Ok(params) => params,
-                    Err(err) => panic!("unable to resolve parameters for op code: {}: {}", op_code_value, err)
-                };
-
-                match process_instruction(&op_code, &params) {
-                    Ok(None) => break,
-                    Ok(Some(value)) => {
-                        let param_layout = op_code.op_type.parameter_layout();
-
-                        if param_layout.has_write {
-                            let write_idx = params[param_layout.num_read] as usize;
-
-                            if write_idx > program_len {
-                                panic!("write address for op code {} refers to out of range program address at instruction pointer={}: {}",
-                                    op_code_value, instruction_ptr, write_idx)
-                            }
-
-                            program[write_idx] = value;
-                        }
-                    },
-                    Err(err) => panic!("error processing value as op code at instruction pointer={}: {}: {}", instruction_ptr, op_code_value, err)
-                }
+        #[test]
+        fn diagnostic_code_after_passing_tests() {
+            assert_eq!(diagnostic_code(&[0, 0, 0, 42]), Ok(42));
+        }
 
-                instruction_ptr += op_code.op_type.num_parameters() + 1;
-            }
+        #[test]
+        fn diagnostic_code_after_failing_test() {
+            assert!(diagnostic_code(&[0, 3, 0, 42]).is_err());
         }
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,11 @@
+use day07::amplifier;
+use day07::amplifier::ChainMode;
+
+fn main() {
+    let program = intcode::input::program_from_arg_file();
+
+    match amplifier::max_thruster_signal(&program, &[5, 6, 7, 8, 9], &ChainMode::FEEDBACK_LOOP) {
+        Ok((signal, phase_settings)) => println!("max thruster signal: {} (phase settings: {:?})", signal, phase_settings),
+        Err(err) => println!("unable to run amplifiers: {}", err)
+    }
+}
@@ -0,0 +1,156 @@
+pub mod amplifier {
+    use intcode::{Machine, RunStatus};
+
+    #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+    pub enum ChainMode {
+        SINGLE_PASS,
+        FEEDBACK_LOOP
+    }
+
+    fn amplifiers(program: &[i64], phase_settings: &[i64]) -> Vec<Machine> {
+        phase_settings.iter().map(|phase_setting| {
+            let mut amplifier = Machine::new(program);
+
+            amplifier.push_input(*phase_setting);
+
+            amplifier
+        }).collect()
+    }
+
+    /// Runs one amplifier per phase setting, each feeding its output to the next, starting with an input signal of 0.
+    /// In feedback loop mode the last amplifier feeds the first until the amplifiers halt. Returns the last signal
+    /// output by the final amplifier.
+    pub fn thruster_signal(program: &[i64], phase_settings: &[i64], mode: &ChainMode) -> Result<i64, String> {
+        let mut amplifiers = amplifiers(program, phase_settings);
+        let mut signal = 0;
+
+        loop {
+            for (idx, amplifier) in amplifiers.iter_mut().enumerate() {
+                amplifier.push_input(signal);
+
+                let status = amplifier.run_until_event().map_err(|err| format!("amplifier {} faulted: {}", idx, err))?;
+
+                match status {
+                    RunStatus::Output(value) => signal = value,
+                    RunStatus::Halted => {
+                        return match mode {
+                            ChainMode::FEEDBACK_LOOP if idx == 0 => Ok(signal),
+                            _ => Err(format!("amplifier {} halted without producing a signal", idx))
+                        };
+                    },
+                    RunStatus::NeedsInput => return Err(format!("amplifier {} needs more input than provided", idx))
+                }
+            }
+
+            if let ChainMode::SINGLE_PASS = mode {
+                return Ok(signal);
+            }
+        }
+    }
+
+    fn permutations(values: &[i64]) -> Vec<Vec<i64>> {
+        if values.len() <= 1 {
+            return vec![values.to_vec()];
+        }
+
+        let mut found = Vec::new();
+
+        for i in 0..values.len() {
+            let mut rest = values.to_vec();
+            let first = rest.remove(i);
+
+            for mut permutation in permutations(&rest) {
+                permutation.insert(0, first);
+
+                found.push(permutation);
+            }
+        }
+
+        found
+    }
+
+    /// Searches every ordering of the given phase settings for the one producing the highest thruster signal.
+    pub fn max_thruster_signal(program: &[i64], phase_settings: &[i64], mode: &ChainMode) -> Result<(i64, Vec<i64>), String> {
+        let mut max: Option<(i64, Vec<i64>)> = None;
+
+        for permutation in permutations(phase_settings) {
+            let signal = thruster_signal(program, &permutation, mode)?;
+
+            match max {
+                Some((max_signal, _)) if max_signal >= signal => (),
+                _ => max = Some((signal, permutation))
+            }
+        }
+
+        max.ok_or_else(|| "no phase settings provided".to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::amplifier::{max_thruster_signal, permutations, thruster_signal, ChainMode};
+
+        const SINGLE_PASS_EXAMPLE_1: [i64; 17] = [3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0];
+        const SINGLE_PASS_EXAMPLE_2: [i64; 25] = [
+            3,23,3,24,1002,24,10,24,1002,23,-1,23,101,5,23,23,1,24,23,23,4,23,99,0,0
+        ];
+        const SINGLE_PASS_EXAMPLE_3: [i64; 34] = [
+            3,31,3,32,1002,32,10,32,1001,31,-2,31,1007,31,0,33,1002,33,7,33,1,33,31,31,1,32,31,31,4,31,99,0,0,0
+        ];
+        const FEEDBACK_LOOP_EXAMPLE_1: [i64; 29] = [
+            3,26,1001,26,-4,26,3,27,1002,27,2,27,1,27,26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5
+        ];
+        const FEEDBACK_LOOP_EXAMPLE_2: [i64; 57] = [
+            3,52,1001,52,-5,52,3,53,1,52,56,54,1007,54,5,55,1005,55,26,1001,54,-5,54,1105,1,12,1,53,54,53,1008,54,0,55,
+            1001,55,1,55,2,53,55,53,4,53,1001,56,-1,56,1005,56,6,99,0,0,0,0,10
+        ];
+
+        fn test_max_signal(program: &[i64], phases: &[i64], mode: ChainMode, expected: i64, expected_phases: &[i64]) {
+            let (signal, phase_settings) = max_thruster_signal(program, phases, &mode).unwrap();
+
+            assert_eq!(signal, expected);
+            assert_eq!(phase_settings, expected_phases);
+        }
+
+        #[test]
+        fn permutations_of_three() {
+            assert_eq!(permutations(&[1, 2, 3]), vec![
+                vec![1, 2, 3], vec![1, 3, 2], vec![2, 1, 3], vec![2, 3, 1], vec![3, 1, 2], vec![3, 2, 1]
+            ]);
+        }
+
+        #[test]
+        fn supplied_single_pass_signal() {
+            assert_eq!(thruster_signal(&SINGLE_PASS_EXAMPLE_1, &[4, 3, 2, 1, 0], &ChainMode::SINGLE_PASS), Ok(43210));
+        }
+
+        #[test]
+        fn supplied_single_pass_test_case_1() {
+            test_max_signal(&SINGLE_PASS_EXAMPLE_1, &[0, 1, 2, 3, 4], ChainMode::SINGLE_PASS, 43210, &[4, 3, 2, 1, 0]);
+        }
+
+        #[test]
+        fn supplied_single_pass_test_case_2() {
+            test_max_signal(&SINGLE_PASS_EXAMPLE_2, &[0, 1, 2, 3, 4], ChainMode::SINGLE_PASS, 54321, &[0, 1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn supplied_single_pass_test_case_3() {
+            test_max_signal(&SINGLE_PASS_EXAMPLE_3, &[0, 1, 2, 3, 4], ChainMode::SINGLE_PASS, 65210, &[1, 0, 4, 3, 2]);
+        }
+
+        #[test]
+        fn supplied_feedback_loop_test_case_1() {
+            test_max_signal(&FEEDBACK_LOOP_EXAMPLE_1, &[5, 6, 7, 8, 9], ChainMode::FEEDBACK_LOOP, 139_629_729, &[9, 8, 7, 6, 5]);
+        }
+
+        #[test]
+        fn supplied_feedback_loop_test_case_2() {
+            test_max_signal(&FEEDBACK_LOOP_EXAMPLE_2, &[5, 6, 7, 8, 9], ChainMode::FEEDBACK_LOOP, 18216, &[9, 7, 8, 5, 6]);
+        }
+
+        #[test]
+        fn single_pass_rejects_halt_without_signal() {
+            assert!(thruster_signal(&[3, 0, 99], &[0, 1], &ChainMode::SINGLE_PASS).is_err());
+        }
+    }
+}
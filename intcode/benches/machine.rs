@@ -0,0 +1,63 @@
+//! Compares run time on a long-running countdown loop with and without the decoded op code cache, and when compiled
+//! ahead of time by `transpile`. Run with `cargo bench`.
+
+#[path = "../tests/transpiled/countdown.rs"]
+mod countdown;
+
+use intcode::transpile::State;
+use intcode::{Machine, RunStatus};
+use std::time::{Duration, Instant};
+
+const ITERATIONS: i64 = 5_000_000;
+const RUNS: u32 = 5;
+
+/// Decrements the counter at address 8 until it reaches zero. `tests/transpiled/countdown.rs` is compiled from it.
+fn countdown(iterations: i64) -> Vec<i64> {
+    vec![1001, 8, -1, 8, 1005, 8, 0, 99, iterations]
+}
+
+fn time_run(program: &[i64], decode_cache: bool) -> (Duration, u64) {
+    let mut machine = Machine::new(program);
+
+    machine.set_decode_cache(decode_cache);
+
+    let start = Instant::now();
+    machine.run().unwrap();
+
+    (start.elapsed(), machine.steps())
+}
+
+fn time_transpiled_run(program: &[i64]) -> Duration {
+    let mut state = State::new(program);
+
+    let start = Instant::now();
+    assert_eq!(countdown::run(&mut state), Ok(RunStatus::Halted));
+
+    start.elapsed()
+}
+
+fn report(label: &str, steps: u64, elapsed: Duration) {
+    println!("{:<16} {:>10} steps in {:>12?} ({:.1}M steps/s)",
+             label, steps, elapsed, steps as f64 / elapsed.as_secs_f64() / 1e6);
+}
+
+fn main() {
+    let program = countdown(ITERATIONS);
+    let mut best = Vec::new();
+    let mut num_steps = 0;
+
+    for decode_cache in &[false, true] {
+        let (elapsed, steps) = (0..RUNS).map(|_| time_run(&program, *decode_cache)).min().unwrap();
+
+        report(if *decode_cache { "decode cache" } else { "no decode cache" }, steps, elapsed);
+        best.push(elapsed);
+        num_steps = steps;
+    }
+
+    let transpiled = (0..RUNS).map(|_| time_transpiled_run(&program)).min().unwrap();
+
+    report("transpiled", num_steps, transpiled);
+
+    println!("speedup from decode cache: {:.2}x", best[0].as_secs_f64() / best[1].as_secs_f64());
+    println!("speedup from transpiling:  {:.2}x", best[1].as_secs_f64() / transpiled.as_secs_f64());
+}
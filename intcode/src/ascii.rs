@@ -0,0 +1,180 @@
+//! ASCII I/O for programs that communicate in text. Input is read a line at a time and fed to the program as character
+//! codes followed by a newline. Outputs below 128 are rendered as characters; any other output is rendered as its
+//! numeric value on a line of its own.
+
+use crate::io::{Input, Output};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{stdin, stdout, BufRead, BufReader, Stdin, Stdout, Write};
+
+const NEWLINE: i64 = b'\n' as i64;
+const ASCII_LIMIT: i64 = 128;
+
+/// Character codes for a line of input, ending with a newline.
+pub fn encode_line(line: &str) -> Result<Vec<i64>, String> {
+    line.chars()
+        .map(|c| if c.is_ascii() { Ok(c as i64) } else { Err(format!("non-ASCII character in input: {:?}", c)) })
+        .chain(std::iter::once(Ok(NEWLINE)))
+        .collect()
+}
+
+fn render_value(value: i64, at_line_start: bool, text: &mut String) {
+    if (0..ASCII_LIMIT).contains(&value) {
+        text.push(value as u8 as char);
+    } else {
+        if !at_line_start {
+            text.push('\n');
+        }
+
+        text.push_str(&format!("{}\n", value));
+    }
+}
+
+/// Renders outputs as text.
+pub fn render(values: &[i64]) -> String {
+    let mut text = String::new();
+
+    for value in values {
+        let at_line_start = text.is_empty() || text.ends_with('\n');
+
+        render_value(*value, at_line_start, &mut text);
+    }
+
+    text
+}
+
+/// Feeds lines read from `R` to the program. End of input is reported as no value available.
+pub struct AsciiInput<R: BufRead> {
+    reader: R,
+    pending: VecDeque<i64>
+}
+
+impl<R: BufRead> AsciiInput<R> {
+    pub fn new(reader: R) -> AsciiInput<R> {
+        AsciiInput { reader, pending: VecDeque::new() }
+    }
+}
+
+impl AsciiInput<BufReader<Stdin>> {
+    /// Reads lines typed at the terminal.
+    pub fn interactive() -> AsciiInput<BufReader<Stdin>> {
+        AsciiInput::new(BufReader::new(stdin()))
+    }
+}
+
+impl AsciiInput<BufReader<File>> {
+    /// Reads commands from a script file, one per line.
+    pub fn scripted(path: &str) -> Result<AsciiInput<BufReader<File>>, String> {
+        let file = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+
+        Ok(AsciiInput::new(BufReader::new(file)))
+    }
+}
+
+impl<R: BufRead> Input for AsciiInput<R> {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        if self.pending.is_empty() {
+            let mut line = String::new();
+
+            if self.reader.read_line(&mut line).map_err(|err| format!("unable to read input line: {}", err))? == 0 {
+                return Ok(None);
+            }
+
+            self.pending.extend(encode_line(line.trim_end_matches(&['\r', '\n'][..]))?);
+        }
+
+        Ok(self.pending.pop_front())
+    }
+}
+
+/// Writes rendered outputs to `W`, flushing after each so that prompts appear before input is read.
+pub struct AsciiOutput<W: Write> {
+    writer: W,
+    at_line_start: bool
+}
+
+impl<W: Write> AsciiOutput<W> {
+    pub fn new(writer: W) -> AsciiOutput<W> {
+        AsciiOutput { writer, at_line_start: true }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl AsciiOutput<Stdout> {
+    pub fn stdout() -> AsciiOutput<Stdout> {
+        AsciiOutput::new(stdout())
+    }
+}
+
+impl<W: Write> Output for AsciiOutput<W> {
+    fn write(&mut self, value: i64) -> Result<(), String> {
+        let mut text = String::new();
+
+        render_value(value, self.at_line_start, &mut text);
+        self.at_line_start = text.ends_with('\n');
+
+        self.writer.write_all(text.as_bytes())
+            .and_then(|_| self.writer.flush())
+            .map_err(|err| format!("unable to write output: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ascii::{encode_line, render, AsciiInput, AsciiOutput};
+    use crate::assembler::assemble;
+    use crate::io::Input;
+    use crate::machine::Machine;
+
+    #[test]
+    fn lines_encoded_with_newline() {
+        assert_eq!(encode_line("go n"), Ok(vec![103, 111, 32, 110, 10]));
+        assert!(encode_line("café").is_err());
+    }
+
+    #[test]
+    fn large_values_rendered_as_numbers() {
+        assert_eq!(render(&[104, 105, 10, 19_349_530, 62, 32]), "hi\n19349530\n> ");
+        assert_eq!(render(&[62, 32, 128, -1]), "> \n128\n-1\n");
+    }
+
+    #[test]
+    fn scripted_input_drains_lines_in_order() {
+        let mut input = AsciiInput::new("ab\r\nc\n".as_bytes());
+        let mut read = Vec::new();
+
+        while let Some(value) = input.read().unwrap() {
+            read.push(value);
+        }
+
+        assert_eq!(read, vec![97, 98, 10, 99, 10]);
+    }
+
+    #[test]
+    fn machine_driven_by_script() {
+        // Echoes a line of lower case input in upper case, then outputs 1000.
+        let program = assemble(concat!(
+            "loop:   IN   [c]\n",
+            "        EQ   [c], #10, [flag]\n",
+            "        JT   [flag], #done\n",
+            "        ADD  [c], #-32, [c]\n",
+            "        OUT  [c]\n",
+            "        JT   #1, #loop\n",
+            "done:   OUT  #10\n",
+            "        OUT  #1000\n",
+            "        HLT\n",
+            "c:      data 0\n",
+            "flag:   data 0\n"
+        )).unwrap();
+        let mut machine = Machine::with_io(&program, AsciiInput::new("hey\n".as_bytes()), AsciiOutput::new(Vec::new()));
+
+        machine.run().unwrap();
+
+        let (_, output) = machine.into_io();
+
+        assert_eq!(String::from_utf8(output.into_inner()).unwrap(), "HEY\n1000\n");
+    }
+}
@@ -0,0 +1,403 @@
+//! Assembles Intcode from a small assembly language:
+//!
+//! ```text
+//! ; comments run to the end of the line
+//! start:  IN   [value]          ; position mode operand
+//!         MUL  [value], #3, rb+2 ; immediate and relative mode operands
+//!         JT   #1, #start       ; labels may be used wherever a number may
+//!         HLT
+//! value:  data 0, -1, start     ; literal words
+//!         org  20               ; continue assembling at address 20, zero-filling the gap
+//! ```
+//!
+//! Mnemonics are those produced by the disassembler and are case insensitive.
+
+use crate::machine::Machine;
+use crate::optype::{OpType, ParameterMode};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssemblyError {
+    pub line: usize,
+    pub message: String
+}
+
+impl Display for AssemblyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+enum Value {
+    Literal(i64),
+    Label(String)
+}
+
+enum Item {
+    Instruction { op_type: OpType, operands: Vec<(ParameterMode, Value)> },
+    Data(Vec<Value>)
+}
+
+struct Statement {
+    line: usize,
+    address: usize,
+    item: Item
+}
+
+fn is_identifier(token: &str) -> bool {
+    let mut chars = token.chars();
+
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => chars.all(|c| c.is_ascii_alphanumeric() || c == '_'),
+        _ => false
+    }
+}
+
+fn parse_value(token: &str) -> Result<Value, String> {
+    let token = token.trim();
+
+    if is_identifier(token) {
+        return Ok(Value::Label(token.to_string()));
+    }
+
+    token.parse::<i64>().map(Value::Literal).map_err(|_| format!("invalid value: {}", token))
+}
+
+fn parse_operand(token: &str) -> Result<(ParameterMode, Value), String> {
+    let token = token.trim();
+
+    if let Some(value) = token.strip_prefix('#') {
+        Ok((ParameterMode::IMMEDIATE, parse_value(value)?))
+    } else if let Some(value) = token.strip_prefix('[').and_then(|value| value.strip_suffix(']')) {
+        Ok((ParameterMode::POSITION, parse_value(value)?))
+    } else if let Some(value) = token.strip_prefix("rb+") {
+        Ok((ParameterMode::RELATIVE, parse_value(value)?))
+    } else if let Some(value) = token.strip_prefix("rb-") {
+        if is_identifier(value.trim()) {
+            return Err(format!("relative operand cannot subtract a label: {}", token));
+        }
+
+        // Parsed with its sign, so that the most negative offset, which has no positive counterpart, can be written.
+        Ok((ParameterMode::RELATIVE, parse_value(&format!("-{}", value.trim()))?))
+    } else {
+        Err(format!("operand must be one of [address], #value or rb+offset: {}", token))
+    }
+}
+
+fn split_operands(operands: &str) -> Vec<&str> {
+    if operands.trim().is_empty() {
+        Vec::new()
+    } else {
+        operands.split(',').map(str::trim).collect()
+    }
+}
+
+/// Labels defined by a line, followed by the keyword and operands of the item it assembles, if any.
+struct SourceLine<'a> {
+    labels: Vec<&'a str>,
+    keyword: &'a str,
+    operands: &'a str
+}
+
+fn parse_line(line: &str) -> Result<SourceLine<'_>, String> {
+    let mut rest = match line.find(';') {
+        Some(idx) => &line[..idx],
+        None => line
+    }.trim();
+
+    let mut labels = Vec::new();
+    while let Some(idx) = rest.find(':') {
+        let label = rest[..idx].trim();
+
+        if !is_identifier(label) {
+            return Err(format!("invalid label: {}", label));
+        }
+
+        labels.push(label);
+        rest = rest[idx + 1..].trim();
+    }
+
+    let (keyword, operands) = match rest.find(char::is_whitespace) {
+        Some(idx) => (&rest[..idx], rest[idx..].trim()),
+        None => (rest, "")
+    };
+
+    Ok(SourceLine { labels, keyword, operands })
+}
+
+fn parse(source: &str) -> Result<(Vec<Statement>, HashMap<String, usize>), AssemblyError> {
+    let mut statements = Vec::new();
+    let mut labels = HashMap::new();
+
+    let mut address = 0;
+    for (idx, line) in source.lines().enumerate() {
+        let line_num = idx + 1;
+        let error = |message: String| AssemblyError { line: line_num, message };
+
+        let SourceLine { labels: line_labels, keyword, operands } = parse_line(line).map_err(error)?;
+        let is_org = keyword.eq_ignore_ascii_case("org");
+
+        // Labels on an org line name the new origin.
+        if is_org {
+            let origin = operands.parse::<usize>().map_err(|_| error(format!("invalid origin: {}", operands)))?;
+
+            if origin < address {
+                return Err(error(format!("origin {} precedes current address {}", origin, address)));
+            }
+
+            if origin >= Machine::DEFAULT_MEMORY_LIMIT {
+                return Err(error(format!("origin {} is beyond the memory limit of {} words", origin, Machine::DEFAULT_MEMORY_LIMIT)));
+            }
+
+            address = origin;
+        }
+
+        for label in line_labels {
+            if labels.insert(label.to_string(), address).is_some() {
+                return Err(error(format!("duplicate label: {}", label)));
+            }
+        }
+
+        if keyword.is_empty() || is_org {
+            continue;
+        }
+
+        let item = if keyword.eq_ignore_ascii_case("data") {
+            let values = split_operands(operands).into_iter().map(parse_value).collect::<Result<Vec<Value>, String>>()
+                .map_err(error)?;
+
+            if values.is_empty() {
+                return Err(error("data directive requires at least one value".to_string()));
+            }
+
+            Item::Data(values)
+        } else {
+            let op_type = OpType::from_mnemonic(keyword).ok_or_else(|| error(format!("unknown mnemonic: {}", keyword)))?;
+            let operands = split_operands(operands).into_iter().map(parse_operand)
+                .collect::<Result<Vec<(ParameterMode, Value)>, String>>()
+                .map_err(error)?;
+
+            if operands.len() != op_type.num_parameters() {
+                return Err(error(format!("{} expects {} operands, found {}",
+                                         op_type.mnemonic(), op_type.num_parameters(), operands.len())));
+            }
+
+            if op_type.parameter_layout().has_write {
+                if let Some((ParameterMode::IMMEDIATE, _)) = operands.last() {
+                    return Err(error(format!("{} cannot write to an immediate operand", op_type.mnemonic())));
+                }
+            }
+
+            Item::Instruction { op_type, operands }
+        };
+
+        let num_words = match &item {
+            Item::Instruction { operands, .. } => operands.len() + 1,
+            Item::Data(values) => values.len()
+        };
+
+        statements.push(Statement { line: line_num, address, item });
+
+        address += num_words;
+    }
+
+    Ok((statements, labels))
+}
+
+fn resolve(value: &Value, labels: &HashMap<String, usize>, line: usize) -> Result<i64, AssemblyError> {
+    match value {
+        Value::Literal(value) => Ok(*value),
+        Value::Label(label) => labels.get(label)
+            .map(|address| *address as i64)
+            .ok_or_else(|| AssemblyError { line, message: format!("undefined label: {}", label) })
+    }
+}
+
+/// Assembles source into a memory image.
+pub fn assemble(source: &str) -> Result<Vec<i64>, AssemblyError> {
+    let (statements, labels) = parse(source)?;
+
+    let mut memory = Vec::new();
+    for statement in statements {
+        memory.resize(statement.address, 0);
+
+        match &statement.item {
+            Item::Instruction { op_type, operands } => {
+                let mut op_code = op_type.value();
+                let mut mode_scale = 100;
+
+                for (mode, _) in operands {
+                    op_code += mode.value() * mode_scale;
+                    mode_scale *= 10;
+                }
+
+                memory.push(op_code);
+
+                for (_, value) in operands {
+                    memory.push(resolve(value, &labels, statement.line)?);
+                }
+            },
+            Item::Data(values) => {
+                for value in values {
+                    memory.push(resolve(value, &labels, statement.line)?);
+                }
+            }
+        }
+    }
+
+    Ok(memory)
+}
+
+/// Formats a memory image in the comma-separated form read by `input::parse_program`.
+pub fn to_program_string(memory: &[i64]) -> String {
+    memory.iter().map(ToString::to_string).collect::<Vec<String>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assembler::{assemble, to_program_string, AssemblyError};
+    use crate::disassembler;
+    use crate::input::parse_program;
+    use crate::Machine;
+
+    fn test_assemble(source: &str, expected: &[i64]) {
+        assert_eq!(assemble(source), Ok(expected.to_vec()));
+    }
+
+    fn test_error(source: &str, line: usize) {
+        assert_eq!(assemble(source).map_err(|err: AssemblyError| err.line), Err(line));
+    }
+
+    #[test]
+    fn assemble_multiple_add() {
+        test_assemble("add [0], [3], [3]\nadd [3], [7], [7]", &[1, 0, 3, 3, 1, 3, 7, 7]);
+    }
+
+    #[test]
+    fn assemble_modes() {
+        test_assemble("MUL rb-2, #3, rb+4\nRBO #-1\nHLT", &[21202, -2, 3, 4, 109, -1, 99]);
+    }
+
+    #[test]
+    fn assemble_labels_and_data() {
+        test_assemble(concat!(
+            "; echo one value\n",
+            "start:  IN [value]\n",
+            "        OUT [value]\n",
+            "        JF #0, #end\n",
+            "value:  data 0\n",
+            "end:    HLT\n"
+        ), &[3, 7, 4, 7, 1106, 0, 8, 0, 99]);
+    }
+
+    #[test]
+    fn forward_reference_in_data() {
+        test_assemble("data a, 5\na: data -3", &[2, 5, -3]);
+    }
+
+    #[test]
+    fn org_zero_fills() {
+        test_assemble("HLT\norg 4\nlast: data last", &[99, 0, 0, 0, 4]);
+    }
+
+    #[test]
+    fn org_label_names_new_origin() {
+        test_assemble("HLT\nend: org 3\ndata end", &[99, 0, 0, 3]);
+    }
+
+    #[test]
+    fn most_negative_relative_offset() {
+        test_assemble("RBO rb-9223372036854775808\nHLT", &[209, i64::MIN, 99]);
+        test_assemble("RBO rb-5\nHLT", &[209, -5, 99]);
+        test_error("RBO rb-start\nstart: HLT", 1);
+    }
+
+    #[test]
+    fn assembled_program_runs() {
+        let program = assemble(concat!(
+            "        IN [n]\n",
+            "loop:   OUT [n]\n",
+            "        ADD [n], #-1, [n]\n",
+            "        JT [n], #loop\n",
+            "        HLT\n",
+            "n:      data 0\n"
+        )).unwrap();
+        let mut machine = Machine::new(&program);
+
+        machine.push_input(3);
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), &[3, 2, 1]);
+    }
+
+    #[test]
+    fn unknown_mnemonic_is_error() {
+        test_error("HLT\nNOP", 2);
+    }
+
+    #[test]
+    fn wrong_operand_count_is_error() {
+        test_error("ADD [1], [2]", 1);
+    }
+
+    #[test]
+    fn immediate_write_is_error() {
+        test_error("IN #4", 1);
+    }
+
+    #[test]
+    fn undefined_label_is_error() {
+        test_error("HLT\n\nJT #1, #nowhere", 3);
+    }
+
+    #[test]
+    fn duplicate_label_is_error() {
+        test_error("a: HLT\na: HLT", 2);
+    }
+
+    #[test]
+    fn backwards_org_is_error() {
+        test_error("data 1, 2, 3\norg 2", 2);
+    }
+
+    #[test]
+    fn org_beyond_memory_limit_is_error() {
+        test_error("HLT\norg 1000000000000000", 2);
+    }
+
+    #[test]
+    fn output_reads_back_as_program() {
+        let program = assemble("ADD #1, #-2, [5]\nHLT").unwrap();
+
+        assert_eq!(parse_program(&to_program_string(&program)), Ok(program));
+    }
+
+    fn test_round_trip(memory: &[i64]) {
+        assert_eq!(assemble(&disassembler::to_assembly(memory)), Ok(memory.to_vec()));
+    }
+
+    #[test]
+    fn round_trip_quine() {
+        test_round_trip(&[109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99]);
+    }
+
+    #[test]
+    fn round_trip_most_negative_relative_offset() {
+        test_round_trip(&[209, i64::MIN, 99]);
+    }
+
+    #[test]
+    fn round_trip_with_data() {
+        test_round_trip(&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8, 10001, 98, 1105]);
+    }
+
+    #[test]
+    fn round_trip_labelled_source() {
+        let source = "start: JT #1, #end\ndata 1, 2, -3\nend: MUL rb-1, [start], rb+7\nHLT";
+
+        test_round_trip(&assemble(source).unwrap());
+    }
+}
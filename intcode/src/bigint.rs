@@ -0,0 +1,269 @@
+//! A minimal signed arbitrary-precision integer, supporting just what the machine needs: addition, multiplication,
+//! comparison, and conversion to and from decimal text.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const DECIMAL_CHUNK: u32 = 1_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 9;
+
+/// Sign and magnitude, the magnitude held as base 2^32 digits, least significant first, with no leading zero digits.
+/// Zero has no digits and is never negative.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    digits: Vec<u32>
+}
+
+fn trim(digits: &mut Vec<u32>) {
+    while digits.last() == Some(&0) {
+        digits.pop();
+    }
+}
+
+fn compare_magnitudes(left: &[u32], right: &[u32]) -> Ordering {
+    left.len().cmp(&right.len()).then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut sum = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0;
+
+    for i in 0..left.len().max(right.len()) {
+        let digit = u64::from(*left.get(i).unwrap_or(&0)) + u64::from(*right.get(i).unwrap_or(&0)) + carry;
+
+        sum.push(digit as u32);
+        carry = digit >> 32;
+    }
+
+    if carry > 0 {
+        sum.push(carry as u32);
+    }
+
+    sum
+}
+
+/// Subtracts the smaller magnitude `right` from `left`.
+fn subtract_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(left.len());
+    let mut borrow = 0;
+
+    for (i, digit) in left.iter().enumerate() {
+        let subtrahend = i64::from(*right.get(i).unwrap_or(&0)) + borrow;
+        let mut digit = i64::from(*digit) - subtrahend;
+
+        borrow = 0;
+        if digit < 0 {
+            digit += 1 << 32;
+            borrow = 1;
+        }
+
+        difference.push(digit as u32);
+    }
+
+    trim(&mut difference);
+
+    difference
+}
+
+fn multiply_magnitudes(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut product = vec![0u32; left.len() + right.len()];
+
+    for (i, left_digit) in left.iter().enumerate() {
+        let mut carry = 0u64;
+
+        for (j, right_digit) in right.iter().enumerate() {
+            let digit = u64::from(product[i + j]) + u64::from(*left_digit) * u64::from(*right_digit) + carry;
+
+            product[i + j] = digit as u32;
+            carry = digit >> 32;
+        }
+
+        product[i + right.len()] = carry as u32;
+    }
+
+    trim(&mut product);
+
+    product
+}
+
+impl BigInt {
+    fn new(negative: bool, mut digits: Vec<u32>) -> BigInt {
+        trim(&mut digits);
+
+        BigInt { negative: negative && !digits.is_empty(), digits }
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitudes(&self.digits, &other.digits));
+        }
+
+        match compare_magnitudes(&self.digits, &other.digits) {
+            Ordering::Less => BigInt::new(other.negative, subtract_magnitudes(&other.digits, &self.digits)),
+            _ => BigInt::new(self.negative, subtract_magnitudes(&self.digits, &other.digits))
+        }
+    }
+
+    pub fn multiply(&self, other: &BigInt) -> BigInt {
+        BigInt::new(self.negative != other.negative, multiply_magnitudes(&self.digits, &other.digits))
+    }
+
+    /// The value as an `i64`, if it fits.
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.digits.len() > 2 {
+            return None;
+        }
+
+        let magnitude = self.digits.iter().rev().fold(0i128, |value, digit| (value << 32) | i128::from(*digit));
+
+        i64::try_from(if self.negative { -magnitude } else { magnitude }).ok()
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> BigInt {
+        let magnitude = value.unsigned_abs();
+
+        BigInt::new(value < 0, vec![magnitude as u32, (magnitude >> 32) as u32])
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.digits, &other.digits),
+            (true, true) => compare_magnitudes(&other.digits, &self.digits)
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for BigInt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut magnitude = self.digits.clone();
+        let mut chunks = Vec::new();
+
+        while !magnitude.is_empty() {
+            let mut remainder = 0u64;
+
+            for digit in magnitude.iter_mut().rev() {
+                let value = (remainder << 32) | u64::from(*digit);
+
+                *digit = (value / u64::from(DECIMAL_CHUNK)) as u32;
+                remainder = value % u64::from(DECIMAL_CHUNK);
+            }
+
+            trim(&mut magnitude);
+            chunks.push(remainder);
+        }
+
+        let mut text = if self.negative { "-".to_string() } else { String::new() };
+
+        match chunks.split_last() {
+            Some((most_significant, rest)) => {
+                text.push_str(&most_significant.to_string());
+
+                for chunk in rest.iter().rev() {
+                    text.push_str(&format!("{:0width$}", chunk, width = DECIMAL_CHUNK_DIGITS));
+                }
+            },
+            None => text.push('0')
+        }
+
+        f.pad(&text)
+    }
+}
+
+impl FromStr for BigInt {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<BigInt, String> {
+        let (negative, decimal) = match text.strip_prefix('-') {
+            Some(decimal) => (true, decimal),
+            None => (false, text.strip_prefix('+').unwrap_or(text))
+        };
+
+        if decimal.is_empty() || !decimal.bytes().all(|byte| byte.is_ascii_digit()) {
+            return Err(format!("invalid digit found in string: {}", text));
+        }
+
+        let mut digits = Vec::new();
+        let first_chunk_len = match decimal.len() % DECIMAL_CHUNK_DIGITS { 0 => DECIMAL_CHUNK_DIGITS, len => len };
+        let mut start = 0;
+        let mut end = first_chunk_len;
+
+        while start < decimal.len() {
+            let chunk = decimal[start..end].parse::<u32>().map_err(|err| err.to_string())?;
+            let scale = 10u64.pow((end - start) as u32);
+            let mut carry = u64::from(chunk);
+
+            for digit in digits.iter_mut() {
+                let value = u64::from(*digit) * scale + carry;
+
+                *digit = value as u32;
+                carry = value >> 32;
+            }
+
+            if carry > 0 {
+                digits.push(carry as u32);
+            }
+
+            start = end;
+            end += DECIMAL_CHUNK_DIGITS;
+        }
+
+        Ok(BigInt::new(negative, digits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bigint::BigInt;
+
+    fn big(text: &str) -> BigInt {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn decimal_round_trip() {
+        for text in &["0", "7", "-42", "4294967296", "-9223372036854775808", "123456789012345678901234567890"] {
+            assert_eq!(big(text).to_string(), *text);
+        }
+
+        assert_eq!(big("-0"), BigInt::default());
+        assert!("12a".parse::<BigInt>().is_err());
+    }
+
+    #[test]
+    fn arithmetic_beyond_i128() {
+        let value = big("170141183460469231731687303715884105727");
+
+        assert_eq!(value.add(&BigInt::from(1)).to_string(), "170141183460469231731687303715884105728");
+        assert_eq!(value.multiply(&big("-2")).to_string(), "-340282366920938463463374607431768211454");
+        assert_eq!(big("-5").add(&big("3")), big("-2"));
+        assert_eq!(big("5").add(&big("-5")), BigInt::default());
+    }
+
+    #[test]
+    fn ordering_and_conversion() {
+        assert!(big("-10") < big("-9"));
+        assert!(big("-1") < big("0"));
+        assert!(big("4294967296") > big("4294967295"));
+        assert_eq!(BigInt::from(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+    }
+}
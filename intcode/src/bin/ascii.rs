@@ -0,0 +1,31 @@
+use intcode::ascii::{AsciiInput, AsciiOutput};
+use intcode::input::load_program;
+use intcode::{IntcodeError, Machine};
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 2 && args.len() != 3 {
+        panic!("usage: ascii <program file> [script file]");
+    }
+
+    let program = match load_program(&args[1]) {
+        Ok(program) => program,
+        Err(err) => panic!("{}", err)
+    };
+
+    let result = match args.get(2) {
+        Some(script) => match AsciiInput::scripted(script) {
+            Ok(input) => Machine::with_io(&program, input, AsciiOutput::stdout()).run(),
+            Err(err) => panic!("{}", err)
+        },
+        None => Machine::with_io(&program, AsciiInput::interactive(), AsciiOutput::stdout()).run()
+    };
+
+    match result {
+        Ok(()) => (),
+        Err(IntcodeError::InputUnavailable { .. }) => println!("(end of input)"),
+        Err(err) => panic!("{}", err)
+    }
+}
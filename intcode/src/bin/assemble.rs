@@ -0,0 +1,14 @@
+use advent_of_code_2019::input;
+use intcode::assembler;
+
+fn main() {
+    let source = input::lines_from_arg_file()
+        .map(|line| line.unwrap())
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    match assembler::assemble(&source) {
+        Ok(program) => println!("{}", assembler::to_program_string(&program)),
+        Err(err) => panic!("unable to assemble program: {}", err)
+    }
+}
@@ -0,0 +1,8 @@
+use intcode::cfg::ControlFlowGraph;
+use intcode::input;
+
+fn main() {
+    let program = input::program_from_arg_file();
+
+    print!("{}", ControlFlowGraph::build(&program).to_dot());
+}
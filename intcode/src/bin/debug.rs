@@ -0,0 +1,12 @@
+use intcode::debugger::Debugger;
+use intcode::input;
+use std::io::{stdin, stdout};
+
+fn main() {
+    let program = input::program_from_arg_file();
+    let mut debugger = Debugger::new(&program);
+
+    if let Err(err) = debugger.repl(stdin().lock(), stdout()) {
+        panic!("debugger i/o failure: {}", err);
+    }
+}
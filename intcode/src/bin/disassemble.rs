@@ -0,0 +1,7 @@
+use intcode::{disassembler, input};
+
+fn main() {
+    let program = input::program_from_arg_file();
+
+    print!("{}", disassembler::listing(&program));
+}
@@ -0,0 +1,22 @@
+use intcode::fixture;
+use std::env;
+use std::process;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 2 {
+        panic!("usage: fixtures <fixture directory>");
+    }
+
+    match fixture::run_directory(&args[1]) {
+        Ok(report) => {
+            print!("{}", report);
+
+            if !report.all_passed() {
+                process::exit(1);
+            }
+        },
+        Err(err) => panic!("{}", err)
+    }
+}
@@ -0,0 +1,34 @@
+use intcode::input::load_program;
+use intcode::io::{StdinInput, StdoutOutput};
+use intcode::profile::Profiler;
+use intcode::Machine;
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 || args.len() > 3 || args.get(2).is_some_and(|format| format != "json") {
+        panic!("usage: profile <program file> [json]");
+    }
+
+    let program = match load_program(&args[1]) {
+        Ok(program) => program,
+        Err(err) => panic!("{}", err)
+    };
+
+    let profiler = Profiler::new();
+    let mut machine = Machine::with_io(&program, profiler.input(StdinInput::new()), profiler.output(StdoutOutput));
+
+    machine.set_tracer(Box::new(profiler.clone()));
+
+    if let Err(err) = machine.run() {
+        eprintln!("{}", err);
+    }
+
+    let report = profiler.report(&program);
+
+    match args.get(2) {
+        Some(_) => println!("{}", report.to_json()),
+        None => print!("{}", report.to_text())
+    }
+}
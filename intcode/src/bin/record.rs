@@ -0,0 +1,32 @@
+use intcode::input::load_program;
+use intcode::io::{StdinInput, StdoutOutput};
+use intcode::session::Recorder;
+use intcode::Machine;
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 3 {
+        panic!("usage: record <program file> <session file>");
+    }
+
+    let program = match load_program(&args[1]) {
+        Ok(program) => program,
+        Err(err) => panic!("{}", err)
+    };
+
+    let recorder = Recorder::new();
+    let result = Machine::with_io(&program, recorder.input(StdinInput::with_prompt("input: ")), recorder.output(StdoutOutput))
+        .run();
+
+    recorder.finish(&result);
+
+    if let Err(err) = recorder.session().save(&args[2]) {
+        panic!("unable to save session: {}", err);
+    }
+
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
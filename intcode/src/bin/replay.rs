@@ -0,0 +1,26 @@
+use intcode::input::load_program;
+use intcode::session::{self, Session};
+use std::env;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() != 3 {
+        panic!("usage: replay <program file> <session file>");
+    }
+
+    let program = match load_program(&args[1]) {
+        Ok(program) => program,
+        Err(err) => panic!("{}", err)
+    };
+
+    let session = match Session::load(&args[2]) {
+        Ok(session) => session,
+        Err(err) => panic!("unable to load session: {}", err)
+    };
+
+    match session::replay(&program, &session) {
+        Ok(()) => println!("replay matched {} events", session.events.len()),
+        Err(err) => panic!("{}", err)
+    }
+}
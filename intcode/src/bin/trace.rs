@@ -0,0 +1,47 @@
+use intcode::input::load_program;
+use intcode::io::{StdinInput, StdoutOutput};
+use intcode::trace::{BinaryTracer, JsonLinesTracer, Tracer};
+use intcode::Machine;
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 3 || args.len() > 4 || args.get(3).is_some_and(|format| format != "json" && format != "binary") {
+        panic!("usage: trace <program file> <trace file> [json|binary]");
+    }
+
+    let program = match load_program(&args[1]) {
+        Ok(program) => program,
+        Err(err) => panic!("{}", err)
+    };
+
+    let writer = match File::create(&args[2]) {
+        Ok(file) => BufWriter::new(file),
+        Err(err) => panic!("{}: {}", args[2], err)
+    };
+
+    let tracer: Box<dyn Tracer> = match args.get(3).map(String::as_str) {
+        Some("binary") => match BinaryTracer::new(writer) {
+            Ok(tracer) => Box::new(tracer),
+            Err(err) => panic!("{}: {}", args[2], err)
+        },
+        _ => Box::new(JsonLinesTracer::new(writer))
+    };
+
+    let mut machine = Machine::with_io(&program, StdinInput::new(), StdoutOutput);
+
+    machine.set_tracer(tracer);
+
+    let result = machine.run();
+
+    if let Some(Err(err)) = machine.take_tracer().map(|mut tracer| tracer.finish()) {
+        panic!("unable to write trace: {}: {}", args[2], err);
+    }
+
+    if let Err(err) = result {
+        panic!("{}", err);
+    }
+}
@@ -0,0 +1,8 @@
+use intcode::input;
+use intcode::transpile::transpile;
+
+fn main() {
+    let program = input::program_from_arg_file();
+
+    print!("{}", transpile(&program));
+}
@@ -0,0 +1,281 @@
+use crate::disassembler::{self, Line, Operand};
+use crate::optype::{OpType, ParameterMode};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A run of instructions entered only at its first and left only after its last. `successors` holds the start
+/// addresses of the blocks control may pass to; `indirect_jump` is set if the final instruction may also jump to a
+/// target only known at run time, and `falls_into_data` if control may run on into words that do not decode as an
+/// instruction. `self_modifying_writes` lists addresses within reachable code that the block's instructions write to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BasicBlock {
+    pub start: usize,
+    pub lines: Vec<Line>,
+    pub successors: Vec<usize>,
+    pub indirect_jump: bool,
+    pub falls_into_data: bool,
+    pub self_modifying_writes: Vec<usize>
+}
+
+impl BasicBlock {
+    /// Address one past the block's last word.
+    pub fn end(&self) -> usize {
+        self.lines.last().map_or(self.start, |line| line.address() + line.num_words())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ControlFlowGraph {
+    pub blocks: Vec<BasicBlock>
+}
+
+/// Control flow leaving an instruction: the addresses it may continue at, and whether it may also jump somewhere
+/// unknown until run time.
+fn exits(line: &Line) -> (Vec<usize>, bool) {
+    let (address, op_type, operands) = match line {
+        Line::Instruction { address, op_type, operands, .. } => (*address, *op_type, operands),
+        Line::Data { .. } => return (Vec::new(), false)
+    };
+    let next = address + line.num_words();
+
+    match op_type {
+        OpType::TERMINATE => (Vec::new(), false),
+        OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE => {
+            let jump_if_nonzero = op_type == OpType::JUMP_IF_TRUE;
+            let mut exits = Vec::new();
+
+            let (may_jump, may_fall_through) = match operands[0] {
+                Operand { mode: ParameterMode::IMMEDIATE, value } => ((value != 0) == jump_if_nonzero, (value != 0) != jump_if_nonzero),
+                _ => (true, true)
+            };
+
+            if may_fall_through {
+                exits.push(next);
+            }
+
+            if may_jump {
+                match operands[1] {
+                    Operand { mode: ParameterMode::IMMEDIATE, value } if value >= 0 => exits.push(value as usize),
+                    Operand { mode: ParameterMode::IMMEDIATE, .. } => (),
+                    _ => return (exits, true)
+                }
+            }
+
+            (exits, false)
+        },
+        _ => (vec![next], false)
+    }
+}
+
+fn ends_block(line: &Line) -> bool {
+    match line {
+        Line::Instruction { op_type, .. } => matches!(op_type, OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE | OpType::TERMINATE),
+        Line::Data { .. } => false
+    }
+}
+
+/// Address written by an instruction, when it is fixed by a position mode parameter.
+fn static_write_address(line: &Line) -> Option<usize> {
+    match line {
+        Line::Instruction { op_type, operands, .. } if op_type.parameter_layout().has_write => match operands.last()? {
+            Operand { mode: ParameterMode::POSITION, value } if *value >= 0 => Some(*value as usize),
+            _ => None
+        },
+        _ => None
+    }
+}
+
+impl ControlFlowGraph {
+    /// Finds the instructions reachable from address 0 by following fall-through and immediate jump targets, and
+    /// splits them into basic blocks.
+    pub fn build(memory: &[i64]) -> ControlFlowGraph {
+        let mut instructions = BTreeMap::new();
+        let mut leaders = BTreeSet::new();
+        let mut pending = vec![0];
+
+        leaders.insert(0);
+
+        while let Some(address) = pending.pop() {
+            if instructions.contains_key(&address) {
+                continue;
+            }
+
+            let line = match disassembler::decode_at(memory, address) {
+                Some(line) => line,
+                None => continue
+            };
+
+            let (exits, _) = exits(&line);
+
+            if ends_block(&line) {
+                leaders.extend(exits.iter().copied());
+                leaders.insert(address + line.num_words());
+            }
+
+            pending.extend(exits);
+            instructions.insert(address, line);
+        }
+
+        let code = instructions.values()
+            .flat_map(|line| line.address()..line.address() + line.num_words())
+            .collect::<BTreeSet<usize>>();
+
+        let mut blocks: Vec<BasicBlock> = Vec::new();
+        for (address, line) in instructions {
+            let continues_block = blocks.last().is_some_and(|block| {
+                block.end() == address && !leaders.contains(&address) && !block.lines.last().is_some_and(ends_block)
+            });
+
+            if !continues_block {
+                blocks.push(BasicBlock {
+                    start: address,
+                    lines: Vec::new(),
+                    successors: Vec::new(),
+                    indirect_jump: false,
+                    falls_into_data: false,
+                    self_modifying_writes: Vec::new()
+                });
+            }
+
+            let block = blocks.last_mut().unwrap();
+
+            if let Some(target) = static_write_address(&line).filter(|target| code.contains(target)) {
+                block.self_modifying_writes.push(target);
+            }
+
+            block.lines.push(line);
+        }
+
+        let starts = blocks.iter().map(|block| block.start).collect::<BTreeSet<usize>>();
+
+        for block in blocks.iter_mut() {
+            let (exits, indirect_jump) = exits(block.lines.last().unwrap());
+
+            block.indirect_jump = indirect_jump;
+
+            for exit in exits {
+                if starts.contains(&exit) {
+                    block.successors.push(exit);
+                } else if exit < memory.len() {
+                    block.falls_into_data = true;
+                }
+            }
+        }
+
+        ControlFlowGraph { blocks }
+    }
+
+    /// The block containing the given address, if it lies within reachable code.
+    pub fn block_containing(&self, address: usize) -> Option<&BasicBlock> {
+        self.blocks.iter().find(|block| block.start <= address && address < block.end())
+    }
+
+    /// Renders the graph in Graphviz DOT format. Blocks that write into code are drawn in red, with a dashed red edge
+    /// to each block they modify.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph intcode {\n    node [shape=box, fontname=\"monospace\"];\n");
+
+        for block in &self.blocks {
+            let mut label = block.lines.iter().map(|line| match line {
+                Line::Instruction { address, op_type, operands, .. } => {
+                    let operands = operands.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ");
+
+                    format!("{}: {:<4} {}", address, op_type.mnemonic(), operands).trim_end().to_string()
+                },
+                Line::Data { address, value } => format!("{}: DATA {}", address, value)
+            }).collect::<Vec<String>>();
+
+            if block.indirect_jump {
+                label.push("(indirect jump)".to_string());
+            }
+
+            if block.falls_into_data {
+                label.push("(continues into data)".to_string());
+            }
+
+            let label = label.iter().map(|line| format!("{}\\l", line.replace('"', "\\\""))).collect::<String>();
+            let style = if block.self_modifying_writes.is_empty() { "" } else { ", color=red, penwidth=2" };
+
+            dot.push_str(&format!("    b{} [label=\"{}\"{}];\n", block.start, label, style));
+        }
+
+        for block in &self.blocks {
+            for successor in &block.successors {
+                dot.push_str(&format!("    b{} -> b{};\n", block.start, successor));
+            }
+
+            for target in &block.self_modifying_writes {
+                if let Some(modified) = self.block_containing(*target) {
+                    dot.push_str(&format!("    b{} -> b{} [style=dashed, color=red, label=\"writes {}\"];\n",
+                                          block.start, modified.start, target));
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cfg::ControlFlowGraph;
+
+    fn block_bounds(memory: &[i64]) -> Vec<(usize, usize, Vec<usize>)> {
+        ControlFlowGraph::build(memory).blocks.iter()
+            .map(|block| (block.start, block.end(), block.successors.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn straight_line_is_one_block() {
+        assert_eq!(block_bounds(&[1101, 1, 2, 9, 4, 9, 99, 0, 0, 0]), vec![(0, 7, vec![])]);
+    }
+
+    #[test]
+    fn conditional_jump_splits_blocks() {
+        // Echoes the input, taking a separate path when it is zero.
+        assert_eq!(block_bounds(&[3, 12, 1005, 12, 9, 1101, 0, 0, 12, 4, 12, 99, 1]), vec![
+            (0, 5, vec![5, 9]),
+            (5, 9, vec![9]),
+            (9, 12, vec![])
+        ]);
+    }
+
+    #[test]
+    fn unconditional_jump_skips_data() {
+        let graph = ControlFlowGraph::build(&[1105, 1, 4, 77, 99]);
+
+        assert_eq!(graph.blocks.iter().map(|block| block.start).collect::<Vec<usize>>(), vec![0, 4]);
+        assert_eq!(graph.blocks[0].successors, vec![4]);
+    }
+
+    #[test]
+    fn position_jump_target_is_indirect() {
+        let graph = ControlFlowGraph::build(&[5, 4, 5, 99, 1, 3]);
+
+        assert!(graph.blocks[0].indirect_jump);
+        assert_eq!(graph.blocks[0].successors, vec![3]);
+    }
+
+    #[test]
+    fn self_modifying_write_detected() {
+        let graph = ControlFlowGraph::build(&[1, 8, 0, 4, 1, 0, 0, 0, 98]);
+
+        assert_eq!(graph.blocks[0].self_modifying_writes, vec![4, 0]);
+        assert!(graph.blocks[0].falls_into_data);
+    }
+
+    #[test]
+    fn dot_output() {
+        assert_eq!(ControlFlowGraph::build(&[1105, 0, 5, 1101, 0, 0, 4, 99]).to_dot(), concat!(
+            "digraph intcode {\n",
+            "    node [shape=box, fontname=\"monospace\"];\n",
+            "    b0 [label=\"0: JT   #0, #5\\l\"];\n",
+            "    b3 [label=\"3: ADD  #0, #0, [4]\\l7: HLT\\l\", color=red, penwidth=2];\n",
+            "    b0 -> b3;\n",
+            "    b3 -> b3 [style=dashed, color=red, label=\"writes 4\"];\n",
+            "}\n"
+        ));
+    }
+}
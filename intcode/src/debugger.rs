@@ -0,0 +1,505 @@
+use crate::disassembler;
+use crate::error::IntcodeError;
+use crate::history::JournalEntry;
+use crate::machine::{Machine, RunStatus};
+use crate::opcode::OpCode;
+use crate::optype::OpType;
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+
+const HISTORY_DEPTH: usize = 100_000;
+const MAX_MEMORY_WORDS: usize = 10_000;
+const CONTINUE_STEP_LIMIT: usize = 1_000_000;
+
+const HELP: &str = "\
+step [n]             execute n instructions (default 1)
+continue             run until a breakpoint, input is needed, the machine halts or loops, or 1000000 steps
+back [n]             undo n instructions (default 1)
+who <addr>           show the last instruction to write to addr
+rewind <addr>        undo instructions up to and including the last write to addr
+break <addr|MNEM>    break at an address or before any instruction with the given mnemonic
+delete <addr|MNEM>   remove a breakpoint
+breakpoints          list breakpoints
+mem <addr> [n]       show n memory words starting at addr (default 1)
+set <addr> <value>   write a value to memory
+regs                 show instruction pointer and relative base
+input <value>...     queue input values
+list [addr] [n]      disassemble n instructions starting at addr (default ip, 5)
+quit                 exit the debugger";
+
+pub struct Debugger {
+    machine: Machine,
+    address_breakpoints: HashSet<usize>,
+    op_type_breakpoints: Vec<OpType>,
+    outputs: Vec<i64>
+}
+
+fn parse_arg<T: std::str::FromStr>(args: &[&str], idx: usize, default: Option<T>) -> Result<T, String> {
+    match args.get(idx) {
+        Some(arg) => arg.parse::<T>().map_err(|_| format!("invalid argument: {}", arg)),
+        None => default.ok_or_else(|| "missing argument".to_string())
+    }
+}
+
+impl Debugger {
+    pub fn new(program: &[i64]) -> Debugger {
+        let mut machine = Machine::new(program);
+
+        machine.enable_history(HISTORY_DEPTH);
+
+        Debugger {
+            machine,
+            address_breakpoints: HashSet::new(),
+            op_type_breakpoints: Vec::new(),
+            outputs: Vec::new()
+        }
+    }
+
+    pub fn machine(&self) -> &Machine { &self.machine }
+    pub fn outputs(&self) -> &[i64] { &self.outputs }
+
+    pub fn add_address_breakpoint(&mut self, address: usize) {
+        self.address_breakpoints.insert(address);
+    }
+
+    pub fn add_op_type_breakpoint(&mut self, op_type: OpType) {
+        if !self.op_type_breakpoints.contains(&op_type) {
+            self.op_type_breakpoints.push(op_type);
+        }
+    }
+
+    fn current_op_type(&self) -> Option<OpType> {
+        OpCode::parse(self.machine.read(self.machine.instruction_ptr())).ok().map(|op_code| op_code.op_type)
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        self.address_breakpoints.contains(&self.machine.instruction_ptr())
+            || self.current_op_type().is_some_and(|op_type| self.op_type_breakpoints.contains(&op_type))
+    }
+
+    fn current_instruction(&self) -> String {
+        let instruction_ptr = self.machine.instruction_ptr();
+
+        match disassembler::decode_at(self.machine.memory(), instruction_ptr) {
+            Some(line) => format!("=> {}", line.to_string().trim()),
+            None => format!("=> {}: {}", instruction_ptr, self.machine.read(instruction_ptr))
+        }
+    }
+
+    /// Executes a single instruction, returning a description of anything notable that happened.
+    fn step_once(&mut self) -> Result<Option<String>, String> {
+        let status = self.machine.step().map_err(|err| match err {
+            IntcodeError::InfiniteLoop { instruction_ptr, .. } => format!("loop detected at ip {}", instruction_ptr),
+            err => err.to_string()
+        })?;
+
+        match status {
+            None => Ok(None),
+            Some(RunStatus::Output(value)) => {
+                self.outputs.push(value);
+
+                Ok(Some(format!("output: {}", value)))
+            },
+            Some(RunStatus::NeedsInput) => Err("waiting for input".to_string()),
+            Some(RunStatus::Halted) => Err("halted".to_string())
+        }
+    }
+
+    /// Steps up to `max_steps` instructions, stopping early at a breakpoint other than the one at which execution
+    /// starts. When no count is given, runs with loop detection enabled for at most `CONTINUE_STEP_LIMIT` steps.
+    fn run(&mut self, max_steps: Option<usize>) -> String {
+        let mut report = Vec::new();
+
+        if max_steps.is_none() {
+            self.machine.enable_loop_detection();
+        }
+
+        let mut steps = 0;
+        loop {
+            match self.step_once() {
+                Ok(Some(event)) => report.push(event),
+                Ok(None) => (),
+                Err(stop) => {
+                    report.push(stop);
+
+                    break;
+                }
+            }
+
+            steps += 1;
+
+            if max_steps.is_some_and(|max_steps| steps >= max_steps) {
+                break;
+            }
+
+            if max_steps.is_none() && steps >= CONTINUE_STEP_LIMIT {
+                report.push(format!("stopped after {} steps", steps));
+
+                break;
+            }
+
+            if self.at_breakpoint() {
+                report.push(format!("breakpoint at {}", self.machine.instruction_ptr()));
+
+                break;
+            }
+        }
+
+        self.machine.disable_loop_detection();
+
+        if !self.machine.is_halted() {
+            report.push(self.current_instruction());
+        }
+
+        report.join("\n")
+    }
+
+    /// Undoes up to `count` instructions, taking back the outputs they produced.
+    fn back(&mut self, count: usize) -> Result<String, String> {
+        for undone in 0..count {
+            match self.machine.step_back() {
+                Some(entry) => if entry.output.is_some() {
+                    self.outputs.pop();
+                },
+                None if undone == 0 => return Err("no history to step back through".to_string()),
+                None => break
+            }
+        }
+
+        Ok(self.current_instruction())
+    }
+
+    fn describe_write(&self, address: usize, entry: &JournalEntry) -> String {
+        let old_value = entry.write.map_or(0, |write| write.old_value);
+        let new_value = entry.write.map_or(0, |write| write.new_value);
+
+        format!("[{}] written at step {} by instruction at {}: {} -> {}",
+                address, entry.step, entry.instruction_ptr, old_value, new_value)
+    }
+
+    fn who(&self, args: &[&str]) -> Result<String, String> {
+        let address = parse_arg::<usize>(args, 0, None)?;
+
+        self.machine.history()
+            .and_then(|history| history.last_write(address))
+            .map(|entry| self.describe_write(address, entry))
+            .ok_or_else(|| format!("no write to {} in history", address))
+    }
+
+    fn rewind(&mut self, args: &[&str]) -> Result<String, String> {
+        let address = parse_arg::<usize>(args, 0, None)?;
+        let entry = self.machine.history()
+            .and_then(|history| history.last_write(address))
+            .cloned()
+            .ok_or_else(|| format!("no write to {} in history", address))?;
+        let description = self.describe_write(address, &entry);
+
+        Ok(format!("{}\n{}", description, self.back((self.machine.steps() - entry.step) as usize)?))
+    }
+
+    fn set_breakpoint(&mut self, target: &str, enable: bool) -> Result<String, String> {
+        if let Ok(address) = target.parse::<usize>() {
+            if enable {
+                self.address_breakpoints.insert(address);
+            } else if !self.address_breakpoints.remove(&address) {
+                return Err(format!("no breakpoint at {}", address));
+            }
+        } else {
+            let op_type = OpType::from_mnemonic(target).ok_or_else(|| format!("unknown address or mnemonic: {}", target))?;
+
+            if enable {
+                self.add_op_type_breakpoint(op_type);
+            } else if let Some(idx) = self.op_type_breakpoints.iter().position(|bp| *bp == op_type) {
+                self.op_type_breakpoints.remove(idx);
+            } else {
+                return Err(format!("no breakpoint on {}", op_type.mnemonic()));
+            }
+        }
+
+        Ok(self.breakpoints())
+    }
+
+    fn breakpoints(&self) -> String {
+        let mut addresses = self.address_breakpoints.iter().collect::<Vec<&usize>>();
+        addresses.sort();
+
+        let addresses = addresses.iter().map(ToString::to_string).collect::<Vec<String>>();
+
+        let op_types = self.op_type_breakpoints.iter().map(OpType::mnemonic).collect::<Vec<&str>>();
+
+        format!("breakpoints: addresses [{}], op codes [{}]", addresses.join(", "), op_types.join(", "))
+    }
+
+    fn memory(&self, args: &[&str]) -> Result<String, String> {
+        let address = parse_arg::<usize>(args, 0, None)?;
+        let count = parse_arg::<usize>(args, 1, Some(1))?;
+
+        if count > MAX_MEMORY_WORDS {
+            return Err(format!("at most {} words can be shown at once", MAX_MEMORY_WORDS));
+        }
+
+        let end = address.checked_add(count).ok_or_else(|| format!("address range overflows: {} + {}", address, count))?;
+
+        Ok((address..end)
+            .map(|address| format!("[{}] = {}", address, self.machine.read(address)))
+            .collect::<Vec<String>>()
+            .join("\n"))
+    }
+
+    fn list(&self, args: &[&str]) -> Result<String, String> {
+        let mut address = parse_arg::<usize>(args, 0, Some(self.machine.instruction_ptr()))?;
+        let count = parse_arg::<usize>(args, 1, Some(5))?;
+
+        let memory = self.machine.memory();
+        let mut lines = Vec::new();
+
+        while lines.len() < count && address < memory.len() {
+            let line = disassembler::decode_at(memory, address)
+                .unwrap_or(disassembler::Line::Data { address, value: memory[address] });
+            let marker = if address == self.machine.instruction_ptr() { "=>" } else { "  " };
+
+            address += line.num_words();
+
+            lines.push(format!("{}{}", marker, line.to_string().trim_end()));
+        }
+
+        Ok(lines.join("\n"))
+    }
+
+    /// Executes one debugger command, returning the text to display.
+    pub fn execute(&mut self, command: &str) -> Result<String, String> {
+        let tokens = command.split_whitespace().collect::<Vec<&str>>();
+        let (name, args) = match tokens.split_first() {
+            Some((name, args)) => (*name, args),
+            None => return Ok(String::new())
+        };
+
+        match name {
+            "s" | "step" => match parse_arg(args, 0, Some(1))? {
+                0 => Err("step count must be at least 1".to_string()),
+                count => Ok(self.run(Some(count)))
+            },
+            "c" | "continue" => Ok(self.run(None)),
+            "bs" | "back" => self.back(parse_arg(args, 0, Some(1))?),
+            "w" | "who" => self.who(args),
+            "rewind" => self.rewind(args),
+            "b" | "break" => self.set_breakpoint(args.first().ok_or("missing argument")?, true),
+            "d" | "delete" => self.set_breakpoint(args.first().ok_or("missing argument")?, false),
+            "breakpoints" => Ok(self.breakpoints()),
+            "x" | "mem" => self.memory(args),
+            "set" => {
+                let address = parse_arg::<usize>(args, 0, None)?;
+                let value = parse_arg::<i64>(args, 1, None)?;
+
+                if address >= self.machine.memory().len() && address >= self.machine.memory_limit() {
+                    return Err(format!("address {} is beyond the memory limit of {} words", address, self.machine.memory_limit()));
+                }
+
+                self.machine.write(address, value);
+
+                Ok(format!("[{}] = {}", address, value))
+            },
+            "r" | "regs" => Ok(format!("ip = {}, rb = {}, halted = {}",
+                                      self.machine.instruction_ptr(), self.machine.relative_base(), self.machine.is_halted())),
+            "i" | "input" => {
+                for idx in 0..args.len() {
+                    let value = parse_arg::<i64>(args, idx, None)?;
+
+                    self.machine.push_input(value);
+                }
+
+                Ok(format!("queued {} input value(s)", args.len()))
+            },
+            "l" | "list" => self.list(args),
+            "h" | "help" => Ok(HELP.to_string()),
+            _ => Err(format!("unknown command: {} (try help)", name))
+        }
+    }
+
+    /// Reads commands from `input` until it is exhausted or `quit` is entered, writing results to `output`.
+    pub fn repl<R: BufRead, W: Write>(&mut self, input: R, mut output: W) -> std::io::Result<()> {
+        writeln!(output, "{}", self.current_instruction())?;
+        write!(output, "(icdb) ")?;
+        output.flush()?;
+
+        for line in input.lines() {
+            let line = line?;
+            let command = line.trim();
+
+            if command == "q" || command == "quit" {
+                break;
+            }
+
+            match self.execute(command) {
+                Ok(text) if text.is_empty() => (),
+                Ok(text) => writeln!(output, "{}", text)?,
+                Err(err) => writeln!(output, "error: {}", err)?
+            }
+
+            write!(output, "(icdb) ")?;
+            output.flush()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::Debugger;
+
+    const COUNTDOWN: [i64; 14] = [3, 13, 4, 13, 1001, 13, -1, 13, 1005, 13, 2, 99, 0, 0];
+
+    #[test]
+    fn step_reports_next_instruction() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 2").unwrap();
+
+        assert_eq!(debugger.execute("step").unwrap(), "=> 2: 4,13                     OUT  [13]");
+        assert_eq!(debugger.machine().instruction_ptr(), 2);
+    }
+
+    #[test]
+    fn continue_stops_at_address_breakpoint() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 3").unwrap();
+        debugger.execute("break 8").unwrap();
+
+        let report = debugger.execute("continue").unwrap();
+
+        assert!(report.starts_with("output: 3\nbreakpoint at 8\n"));
+        assert_eq!(debugger.machine().read(13), 2);
+    }
+
+    #[test]
+    fn continue_stops_at_op_code_breakpoint() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 2").unwrap();
+        debugger.execute("break OUT").unwrap();
+        debugger.execute("continue").unwrap();
+
+        assert_eq!(debugger.machine().instruction_ptr(), 2);
+
+        debugger.execute("continue").unwrap();
+
+        assert_eq!(debugger.machine().instruction_ptr(), 2);
+        assert_eq!(debugger.outputs(), &[2]);
+    }
+
+    #[test]
+    fn continue_without_input_waits() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        assert!(debugger.execute("continue").unwrap().starts_with("waiting for input"));
+
+        debugger.execute("input 1").unwrap();
+
+        assert_eq!(debugger.execute("c").unwrap(), "output: 1\nhalted");
+    }
+
+    #[test]
+    fn continue_stops_at_infinite_loop() {
+        let mut debugger = Debugger::new(&[1105, 1, 0]);
+
+        assert_eq!(debugger.execute("c").unwrap(), "loop detected at ip 0\n=> 0: 1105,1,0                 JT   #1, #0");
+    }
+
+    #[test]
+    fn continue_stops_after_step_limit() {
+        // Counts up forever.
+        let mut debugger = Debugger::new(&[1001, 7, 1, 7, 1105, 1, 0, 0]);
+
+        assert!(debugger.execute("c").unwrap().starts_with("stopped after 1000000 steps\n"));
+        assert_eq!(debugger.execute("mem 7").unwrap(), "[7] = 500000");
+    }
+
+    #[test]
+    fn deleted_breakpoint_is_ignored() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 2").unwrap();
+        debugger.execute("b 8").unwrap();
+        debugger.execute("d 8").unwrap();
+
+        assert!(debugger.execute("c").unwrap().ends_with("halted"));
+        assert_eq!(debugger.outputs(), &[2, 1]);
+    }
+
+    #[test]
+    fn memory_modification() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("set 13 7").unwrap();
+
+        assert_eq!(debugger.execute("mem 12 2").unwrap(), "[12] = 0\n[13] = 7");
+        assert!(debugger.execute("set 1000000000000000 1").is_err());
+    }
+
+    #[test]
+    fn registers() {
+        let mut debugger = Debugger::new(&[109, -3, 99]);
+
+        debugger.execute("step").unwrap();
+
+        assert_eq!(debugger.execute("regs").unwrap(), "ip = 2, rb = -3, halted = false");
+    }
+
+    #[test]
+    fn step_back_takes_back_output() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 2").unwrap();
+        debugger.execute("step 2").unwrap();
+
+        assert_eq!(debugger.outputs(), &[2]);
+        assert_eq!(debugger.execute("back").unwrap(), "=> 2: 4,13                     OUT  [13]");
+        assert!(debugger.outputs().is_empty());
+        assert!(debugger.execute("back 5").unwrap().starts_with("=> 0:"));
+        assert!(debugger.execute("back").is_err());
+    }
+
+    #[test]
+    fn who_wrote_and_rewind() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        debugger.execute("input 2").unwrap();
+        debugger.execute("continue").unwrap();
+
+        assert_eq!(debugger.execute("who 13").unwrap(), "[13] written at step 5 by instruction at 4: 1 -> 0");
+        assert_eq!(debugger.execute("rewind 13").unwrap(),
+                   "[13] written at step 5 by instruction at 4: 1 -> 0\n=> 4: 1001,13,-1,13            ADD  [13], #-1, [13]");
+        assert_eq!(debugger.machine().read(13), 1);
+        assert_eq!(debugger.outputs(), &[2, 1]);
+        assert!(debugger.execute("who 12").is_err());
+    }
+
+    #[test]
+    fn invalid_commands_are_errors() {
+        let mut debugger = Debugger::new(&COUNTDOWN);
+
+        assert!(debugger.execute("bogus").is_err());
+        assert!(debugger.execute("break NOP").is_err());
+        assert!(debugger.execute("mem x").is_err());
+        assert!(debugger.execute("mem 18446744073709551615 2").is_err());
+        assert!(debugger.execute("mem 0 18446744073709551615").is_err());
+        assert!(debugger.execute("step 0").is_err());
+        assert_eq!(debugger.machine().steps(), 0);
+        assert!(debugger.execute("delete 4").is_err());
+    }
+
+    #[test]
+    fn repl_session() {
+        let mut debugger = Debugger::new(&[104, 5, 99]);
+        let mut output = Vec::new();
+
+        debugger.repl("step\nquit\nstep\n".as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("output: 5\n=> 2: 99"));
+        assert_eq!(debugger.outputs(), &[5]);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::opcode::OpCode;
+use crate::optype::{OpType, ParameterMode};
+use std::fmt::{Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Operand {
+    pub mode: ParameterMode,
+    pub value: i64
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.mode {
+            ParameterMode::POSITION => write!(f, "[{}]", self.value),
+            ParameterMode::IMMEDIATE => write!(f, "#{}", self.value),
+            ParameterMode::RELATIVE if self.value < 0 => write!(f, "rb{}", self.value),
+            ParameterMode::RELATIVE => write!(f, "rb+{}", self.value)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Line {
+    Instruction { address: usize, words: Vec<i64>, op_type: OpType, operands: Vec<Operand> },
+    Data { address: usize, value: i64 }
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address
+        }
+    }
+
+    pub fn num_words(&self) -> usize {
+        match self {
+            Line::Instruction { words, .. } => words.len(),
+            Line::Data { .. } => 1
+        }
+    }
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Line::Instruction { address, words, op_type, operands } => {
+                let words = words.iter().map(ToString::to_string).collect::<Vec<String>>().join(",");
+                let operands = operands.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ");
+
+                write!(f, "{:>6}: {:<24} {:<4} {}", address, words, op_type.mnemonic(), operands)?;
+            },
+            Line::Data { address, value } => write!(f, "{:>6}: {:<24} DATA {}", address, value, value)?
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes the instruction at the given address, or `None` if the word there is not a valid op code or the
+/// instruction's parameters would extend past the end of memory.
+pub fn decode_at(memory: &[i64], address: usize) -> Option<Line> {
+    let op_code = OpCode::parse(*memory.get(address)?).ok()?;
+    let words = memory.get(address..address + op_code.instruction_len())?;
+
+    let operands = op_code.param_modes().iter()
+        .zip(&words[1..])
+        .map(|(mode, value)| Operand { mode: *mode, value: *value })
+        .collect();
+
+    Some(Line::Instruction { address, words: words.to_vec(), op_type: op_code.op_type, operands })
+}
+
+/// Walks memory from address 0, decoding one instruction at a time. Words that cannot be decoded as an instruction
+/// are emitted as data, one word at a time, until a decodable instruction is found again.
+pub fn disassemble(memory: &[i64]) -> Vec<Line> {
+    let mut lines = Vec::new();
+
+    let mut address = 0;
+    while address < memory.len() {
+        let line = decode_at(memory, address).unwrap_or(Line::Data { address, value: memory[address] });
+
+        address += line.num_words();
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Disassembles memory into a listing with one instruction per line.
+pub fn listing(memory: &[i64]) -> String {
+    disassemble(memory).iter().map(|line| format!("{}\n", line.to_string().trim_end())).collect()
+}
+
+/// Disassembles memory into source accepted by the assembler, such that assembling it reproduces memory exactly.
+pub fn to_assembly(memory: &[i64]) -> String {
+    disassemble(memory).iter().map(|line| match line {
+        Line::Instruction { op_type, operands, .. } => {
+            let operands = operands.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ");
+
+            format!("{}\n", format!("{:<4} {}", op_type.mnemonic(), operands).trim_end())
+        },
+        Line::Data { value, .. } => format!("data {}\n", value)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::disassembler::{disassemble, listing, to_assembly, Line, Operand};
+    use crate::optype::{OpType, ParameterMode};
+
+    #[test]
+    fn decode_modes() {
+        assert_eq!(disassemble(&[21101, 5, -2, 3]), vec![Line::Instruction {
+            address: 0,
+            words: vec![21101, 5, -2, 3],
+            op_type: OpType::ADD,
+            operands: vec![
+                Operand { mode: ParameterMode::IMMEDIATE, value: 5 },
+                Operand { mode: ParameterMode::IMMEDIATE, value: -2 },
+                Operand { mode: ParameterMode::RELATIVE, value: 3 }
+            ]
+        }]);
+    }
+
+    #[test]
+    fn listing_format() {
+        assert_eq!(listing(&[1002, 4, 3, 4, 33, 209, -1, 99]), concat!(
+            "     0: 1002,4,3,4               MUL  [4], #3, [4]\n",
+            "     4: 33                       DATA 33\n",
+            "     5: 209,-1                   RBO  rb-1\n",
+            "     7: 99                       HLT\n"
+        ));
+    }
+
+    #[test]
+    fn assembly_format() {
+        assert_eq!(to_assembly(&[1002, 4, 3, 4, 33, 99]), "MUL  [4], #3, [4]\ndata 33\nHLT\n");
+    }
+
+    #[test]
+    fn invalid_op_codes_are_data() {
+        assert_eq!(disassemble(&[-5, 10001, 104, 7]).iter().map(Line::num_words).collect::<Vec<usize>>(), vec![1, 1, 2]);
+    }
+
+    #[test]
+    fn truncated_instruction_is_data() {
+        assert_eq!(disassemble(&[99, 1, 2]), vec![
+            Line::Instruction { address: 0, words: vec![99], op_type: OpType::TERMINATE, operands: vec![] },
+            Line::Data { address: 1, value: 1 },
+            Line::Data { address: 2, value: 2 }
+        ]);
+    }
+}
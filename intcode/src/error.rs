@@ -0,0 +1,84 @@
+use std::fmt::{Display, Formatter};
+
+/// Reason a value could not be decoded as an op code.
+#[derive(Clone, Debug, PartialEq)]
+pub enum OpCodeError {
+    Negative,
+    UnknownOpType(i64),
+    InvalidParameterMode { param_idx: usize, mode: i64 },
+    ExcessParameterModes { found: usize, expected: usize },
+    ImmediateWrite { param_idx: usize }
+}
+
+impl Display for OpCodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OpCodeError::Negative => write!(f, "negative value cannot be an op code"),
+            OpCodeError::UnknownOpType(op_type) => write!(f, "invalid op code identifier: {}", op_type),
+            OpCodeError::InvalidParameterMode { param_idx, mode } =>
+                write!(f, "invalid parameter mode at index {}: {}", param_idx, mode),
+            OpCodeError::ExcessParameterModes { found, expected } =>
+                write!(f, "number of parameter modes specified in op code ({}) exceeds number expected ({})", found, expected),
+            OpCodeError::ImmediateWrite { param_idx } =>
+                write!(f, "write parameter at index {} specified in op code as immediate", param_idx)
+        }
+    }
+}
+
+impl std::error::Error for OpCodeError {}
+
+/// Fault raised while executing an Intcode program.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IntcodeError {
+    InvalidOpCode { instruction_ptr: usize, op_code: i64, cause: OpCodeError },
+    NegativeAddress { instruction_ptr: usize, op_code: i64, param_idx: usize, address: i64 },
+    AddressOutOfRange { instruction_ptr: usize, op_code: i64, param_idx: usize, address: usize, limit: usize },
+    InputUnavailable { instruction_ptr: usize },
+    Io { instruction_ptr: usize, op_code: i64, message: String },
+    StepBudgetExhausted { instruction_ptr: usize, budget: u64 },
+    InfiniteLoop { instruction_ptr: usize, first_step: u64, repeat_step: u64 },
+    Overflow { instruction_ptr: usize, op_code: i64 }
+}
+
+impl IntcodeError {
+    pub fn instruction_ptr(&self) -> usize {
+        match self {
+            IntcodeError::InvalidOpCode { instruction_ptr, .. }
+            | IntcodeError::NegativeAddress { instruction_ptr, .. }
+            | IntcodeError::AddressOutOfRange { instruction_ptr, .. }
+            | IntcodeError::InputUnavailable { instruction_ptr }
+            | IntcodeError::Io { instruction_ptr, .. }
+            | IntcodeError::StepBudgetExhausted { instruction_ptr, .. }
+            | IntcodeError::InfiniteLoop { instruction_ptr, .. }
+            | IntcodeError::Overflow { instruction_ptr, .. } => *instruction_ptr
+        }
+    }
+}
+
+impl Display for IntcodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntcodeError::InvalidOpCode { instruction_ptr, op_code, cause } =>
+                write!(f, "invalid op code at instruction pointer={}: {}: {}", instruction_ptr, op_code, cause),
+            IntcodeError::NegativeAddress { instruction_ptr, op_code, param_idx, address } =>
+                write!(f, "parameter index {} of op code {} at instruction pointer={} refers to negative address: {}",
+                       param_idx, op_code, instruction_ptr, address),
+            IntcodeError::AddressOutOfRange { instruction_ptr, op_code, param_idx, address, limit } =>
+                write!(f, "parameter index {} of op code {} at instruction pointer={} writes to address {} beyond the memory \
+                           limit of {} words", param_idx, op_code, instruction_ptr, address, limit),
+            IntcodeError::InputUnavailable { instruction_ptr } =>
+                write!(f, "no input value available at instruction pointer={}", instruction_ptr),
+            IntcodeError::Io { instruction_ptr, op_code, message } =>
+                write!(f, "i/o failure for op code {} at instruction pointer={}: {}", op_code, instruction_ptr, message),
+            IntcodeError::StepBudgetExhausted { instruction_ptr, budget } =>
+                write!(f, "step budget of {} instructions exhausted at instruction pointer={}", budget, instruction_ptr),
+            IntcodeError::InfiniteLoop { instruction_ptr, first_step, repeat_step } =>
+                write!(f, "infinite loop: machine state at instruction pointer={} after step {} repeats that after step {} \
+                           without any intervening input or output", instruction_ptr, repeat_step, first_step),
+            IntcodeError::Overflow { instruction_ptr, op_code } =>
+                write!(f, "arithmetic overflow in op code {} at instruction pointer={}", op_code, instruction_ptr)
+        }
+    }
+}
+
+impl std::error::Error for IntcodeError {}
@@ -0,0 +1,340 @@
+//! Regression tests kept as data. A fixture is a text file with a version header followed by one `key value` line per
+//! field; blank lines and lines starting with `#` are ignored:
+//!
+//! ```text
+//! intcode-fixture 1
+//! # the program, and values written over it before it runs
+//! program 1,9,10,3,2,3,11,0,99,30,40,50
+//! patch 1 9
+//! # expectations: the whole of final memory, and single addresses
+//! memory 3500,9,10,70,2,3,11,0,99,30,40,50
+//! cell 0 3500
+//! ```
+//!
+//! Programs that do I/O take an `input` line of values to read, and an `output` line of the values they are expected
+//! to write. `patch` and `cell` may be repeated. Every fixture needs a program and at least one expectation. Fixtures
+//! are found by `run_directory`, which runs every file with the `.fixture` extension in a directory.
+
+use crate::error::IntcodeError;
+use crate::input::parse_program;
+use crate::machine::Machine;
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+const HEADER: &str = "intcode-fixture 1";
+const EXTENSION: &str = "fixture";
+
+/// Limit on the instructions a fixture may execute, so that a program that never halts fails rather than hangs.
+const STEP_BUDGET: u64 = 10_000_000;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Fixture {
+    pub program: Vec<i64>,
+    pub patches: Vec<(usize, i64)>,
+    pub input: Vec<i64>,
+    pub expected_output: Option<Vec<i64>>,
+    pub expected_memory: Option<Vec<i64>>,
+    pub expected_cells: Vec<(usize, i64)>
+}
+
+/// A way in which a fixture's run differed from its expectations.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mismatch {
+    Fault(IntcodeError),
+    Output { expected: Vec<i64>, actual: Vec<i64> },
+    Memory { expected: Vec<i64>, actual: Vec<i64> },
+    Cell { address: usize, expected: i64, actual: i64 }
+}
+
+fn parse_list(value: &str) -> Result<Vec<i64>, String> {
+    if value.is_empty() {
+        Ok(Vec::new())
+    } else {
+        parse_program(value)
+    }
+}
+
+fn parse_pair(value: &str) -> Result<(usize, i64), String> {
+    let (address, value) = value.split_once(' ').ok_or_else(|| "expected an address and a value".to_string())?;
+    let address = address.parse().map_err(|_| format!("invalid address: {}", address))?;
+    let value = value.trim().parse().map_err(|_| format!("invalid value: {}", value.trim()))?;
+
+    Ok((address, value))
+}
+
+/// Describes where two sequences first differ.
+fn sequence_difference(what: &str, index_name: &str, expected: &[i64], actual: &[i64]) -> String {
+    match expected.iter().zip(actual).position(|(expected, actual)| expected != actual) {
+        Some(idx) => format!("{} differs at {} {}: expected {}, found {}", what, index_name, idx, expected[idx], actual[idx]),
+        None if expected.len() < actual.len() =>
+            format!("{} has {} unexpected trailing values, starting with {}", what, actual.len() - expected.len(), actual[expected.len()]),
+        None => format!("{} ends after {} values, expected {} more, starting with {}",
+                        what, actual.len(), expected.len() - actual.len(), expected[actual.len()])
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mismatch::Fault(error) => write!(f, "faulted: {}", error),
+            Mismatch::Output { expected, actual } => write!(f, "{}", sequence_difference("output", "index", expected, actual)),
+            Mismatch::Memory { expected, actual } => write!(f, "{}", sequence_difference("memory", "address", expected, actual)),
+            Mismatch::Cell { address, expected, actual } =>
+                write!(f, "memory at address {}: expected {}, found {}", address, expected, actual)
+        }
+    }
+}
+
+impl Fixture {
+    pub fn parse(text: &str) -> Result<Fixture, String> {
+        let mut lines = text.lines().enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        match lines.next() {
+            Some((_, HEADER)) => (),
+            Some((_, line)) => return Err(format!("unsupported fixture header: {}", line)),
+            None => return Err("empty fixture".to_string())
+        }
+
+        let mut fixture = Fixture::default();
+        let mut program = None;
+        let mut input = None;
+
+        for (line_num, line) in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let value = value.trim();
+            let at_line = |err: String| format!("line {}: {}", line_num, err);
+
+            let duplicate = match key {
+                "program" => program.replace(parse_program(value).map_err(at_line)?).is_some(),
+                "patch" => {
+                    let (address, value) = parse_pair(value).map_err(at_line)?;
+
+                    if address >= Machine::DEFAULT_MEMORY_LIMIT {
+                        return Err(at_line(format!("patch address {} is beyond the memory limit of {} words",
+                                                   address, Machine::DEFAULT_MEMORY_LIMIT)));
+                    }
+
+                    fixture.patches.push((address, value));
+                    false
+                },
+                "input" => input.replace(parse_list(value).map_err(at_line)?).is_some(),
+                "output" => fixture.expected_output.replace(parse_list(value).map_err(at_line)?).is_some(),
+                "memory" => fixture.expected_memory.replace(parse_program(value).map_err(at_line)?).is_some(),
+                "cell" => {
+                    fixture.expected_cells.push(parse_pair(value).map_err(at_line)?);
+                    false
+                },
+                _ => return Err(at_line(format!("unknown fixture field: {}", key)))
+            };
+
+            if duplicate {
+                return Err(at_line(format!("duplicate fixture field: {}", key)));
+            }
+        }
+
+        fixture.program = program.ok_or_else(|| "missing fixture field: program".to_string())?;
+        fixture.input = input.unwrap_or_default();
+
+        if fixture.expected_output.is_none() && fixture.expected_memory.is_none() && fixture.expected_cells.is_empty() {
+            return Err("fixture has no expectations".to_string());
+        }
+
+        Ok(fixture)
+    }
+
+    pub fn load(path: &str) -> Result<Fixture, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+
+        Fixture::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    /// Runs the program to completion, returning every way in which the run differed from the expectations. A program
+    /// that faults is checked no further.
+    pub fn run(&self) -> Vec<Mismatch> {
+        let mut machine = Machine::new(&self.program);
+
+        for (address, value) in &self.patches {
+            machine.write(*address, *value);
+        }
+
+        for value in &self.input {
+            machine.push_input(*value);
+        }
+
+        machine.set_step_budget(Some(STEP_BUDGET));
+
+        if let Err(error) = machine.run() {
+            return vec![Mismatch::Fault(error)];
+        }
+
+        let mut mismatches = Vec::new();
+
+        if let Some(expected) = self.expected_output.as_ref().filter(|expected| expected.as_slice() != machine.outputs()) {
+            mismatches.push(Mismatch::Output { expected: expected.clone(), actual: machine.outputs().to_vec() });
+        }
+
+        if let Some(expected) = self.expected_memory.as_ref().filter(|expected| expected.as_slice() != machine.memory()) {
+            mismatches.push(Mismatch::Memory { expected: expected.clone(), actual: machine.memory().to_vec() });
+        }
+
+        for (address, expected) in &self.expected_cells {
+            let actual = machine.read(*address);
+
+            if actual != *expected {
+                mismatches.push(Mismatch::Cell { address: *address, expected: *expected, actual });
+            }
+        }
+
+        mismatches
+    }
+}
+
+/// Outcome of one fixture file: the ways its run differed from its expectations, or why it could not be loaded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureResult {
+    pub path: String,
+    pub outcome: Result<Vec<Mismatch>, String>
+}
+
+impl FixtureResult {
+    pub fn passed(&self) -> bool {
+        matches!(&self.outcome, Ok(mismatches) if mismatches.is_empty())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FixtureReport {
+    pub results: Vec<FixtureResult>
+}
+
+impl FixtureReport {
+    pub fn num_passed(&self) -> usize {
+        self.results.iter().filter(|result| result.passed()).count()
+    }
+
+    pub fn all_passed(&self) -> bool {
+        self.num_passed() == self.results.len()
+    }
+}
+
+impl Display for FixtureReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for result in &self.results {
+            match &result.outcome {
+                Ok(mismatches) if mismatches.is_empty() => writeln!(f, "ok   {}", result.path)?,
+                Ok(mismatches) => {
+                    writeln!(f, "FAIL {}", result.path)?;
+
+                    for mismatch in mismatches {
+                        writeln!(f, "       {}", mismatch)?;
+                    }
+                },
+                Err(err) => {
+                    writeln!(f, "FAIL {}", result.path)?;
+                    writeln!(f, "       unable to load: {}", err)?;
+                }
+            }
+        }
+
+        writeln!(f, "{} passed, {} failed", self.num_passed(), self.results.len() - self.num_passed())
+    }
+}
+
+/// Paths of the fixture files in a directory, in name order.
+pub fn discover(dir: &str) -> Result<Vec<String>, String> {
+    let mut paths = fs::read_dir(dir).map_err(|err| format!("{}: {}", dir, err))?
+        .map(|entry| entry.map(|entry| entry.path()).map_err(|err| format!("{}: {}", dir, err)))
+        .collect::<Result<Vec<_>, String>>()?
+        .into_iter()
+        .filter(|path| path.is_file() && path.extension().is_some_and(|extension| extension == EXTENSION))
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect::<Vec<String>>();
+
+    paths.sort();
+
+    Ok(paths)
+}
+
+/// Runs every fixture in a directory.
+pub fn run_directory(dir: &str) -> Result<FixtureReport, String> {
+    let results = discover(dir)?.into_iter()
+        .map(|path| {
+            let outcome = Fixture::load(&path).map(|fixture| fixture.run());
+
+            FixtureResult { path, outcome }
+        })
+        .collect();
+
+    Ok(FixtureReport { results })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::IntcodeError;
+    use crate::fixture::{Fixture, Mismatch};
+
+    const NOUN_VERB: &str = "intcode-fixture 1\n\
+                             # day 2 example with its noun replaced\n\
+                             program 1,9,10,3,2,3,11,0,99,30,40,50\n\
+                             patch 1 10\n\
+                             cell 0 4000\n\
+                             memory 4000,10,10,80,2,3,11,0,99,30,40,50\n";
+
+    #[test]
+    fn parse_fixture() {
+        assert_eq!(Fixture::parse(NOUN_VERB), Ok(Fixture {
+            program: vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50],
+            patches: vec![(1, 10)],
+            input: Vec::new(),
+            expected_output: None,
+            expected_memory: Some(vec![4000, 10, 10, 80, 2, 3, 11, 0, 99, 30, 40, 50]),
+            expected_cells: vec![(0, 4000)]
+        }));
+    }
+
+    #[test]
+    fn invalid_fixture_is_error() {
+        assert!(Fixture::parse("intcode-fixture 2\nprogram 99\noutput\n").is_err());
+        assert!(Fixture::parse("intcode-fixture 1\nprogram 99\n").is_err());
+        assert!(Fixture::parse("intcode-fixture 1\noutput 1\n").is_err());
+        assert!(Fixture::parse("intcode-fixture 1\nprogram 99\nprogram 99\noutput\n").is_err());
+        assert_eq!(Fixture::parse("intcode-fixture 1\nprogram 3,0,4,0,99\ninput 1\ninput 2\noutput 2\n"),
+                   Err("line 4: duplicate fixture field: input".to_string()));
+        assert_eq!(Fixture::parse("intcode-fixture 1\nprogram 99\npatch 1\n"), Err("line 3: expected an address and a value".to_string()));
+        assert!(Fixture::parse("intcode-fixture 1\nprogram 99\npatch 1000000000000000 1\noutput\n").is_err());
+    }
+
+    #[test]
+    fn passing_fixture_has_no_mismatches() {
+        assert_eq!(Fixture::parse(NOUN_VERB).unwrap().run(), vec![]);
+        assert_eq!(Fixture::parse("intcode-fixture 1\nprogram 3,0,4,0,99\ninput 42\noutput 42\n").unwrap().run(), vec![]);
+    }
+
+    #[test]
+    fn mismatches_described() {
+        let fixture = Fixture::parse("intcode-fixture 1\nprogram 104,1,104,2,99\noutput 1,3\ncell 1 2\nmemory 104,1,104\n").unwrap();
+        let mismatches = fixture.run().iter().map(ToString::to_string).collect::<Vec<String>>();
+
+        assert_eq!(mismatches, vec![
+            "output differs at index 1: expected 3, found 2",
+            "memory has 2 unexpected trailing values, starting with 2",
+            "memory at address 1: expected 2, found 1"
+        ]);
+    }
+
+    #[test]
+    fn missing_output_described() {
+        let fixture = Fixture::parse("intcode-fixture 1\nprogram 104,1,99\noutput 1,2,3\n").unwrap();
+
+        assert_eq!(fixture.run()[0].to_string(), "output ends after 1 values, expected 2 more, starting with 2");
+    }
+
+    #[test]
+    fn fault_reported() {
+        let fixture = Fixture::parse("intcode-fixture 1\nprogram 1105,1,0\noutput\n").unwrap();
+
+        assert_eq!(fixture.run(), vec![Mismatch::Fault(IntcodeError::StepBudgetExhausted { instruction_ptr: 0, budget: 10_000_000 })]);
+    }
+}
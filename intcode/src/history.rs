@@ -0,0 +1,142 @@
+use crate::trace::MemoryWrite;
+use std::collections::VecDeque;
+
+/// The state changed by one executed instruction, sufficient to undo it. `memory_len` is the length of memory before
+/// the instruction executed, so that memory grown by its write can be shrunk again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JournalEntry<W = i64> {
+    pub step: u64,
+    pub instruction_ptr: usize,
+    pub relative_base: i64,
+    pub memory_len: usize,
+    pub write: Option<MemoryWrite<W>>,
+    pub input: Option<W>,
+    pub output: Option<W>
+}
+
+/// Journal of the most recently executed instructions, discarding the oldest once `depth` entries are held.
+#[derive(Clone, Debug)]
+pub struct History<W = i64> {
+    depth: usize,
+    entries: VecDeque<JournalEntry<W>>
+}
+
+impl<W> History<W> {
+    pub fn new(depth: usize) -> History<W> {
+        History { depth, entries: VecDeque::new() }
+    }
+
+    pub fn depth(&self) -> usize { self.depth }
+    pub fn len(&self) -> usize { self.entries.len() }
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+    /// Journal entries, oldest first.
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &JournalEntry<W>> {
+        self.entries.iter()
+    }
+
+    /// The most recent instruction still in the journal to have written to the given address.
+    pub fn last_write(&self, address: usize) -> Option<&JournalEntry<W>> {
+        self.entries.iter().rev().find(|entry| entry.write.as_ref().is_some_and(|write| write.address == address))
+    }
+
+    pub(crate) fn push(&mut self, entry: JournalEntry<W>) {
+        if self.depth == 0 {
+            return;
+        }
+
+        if self.entries.len() == self.depth {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<JournalEntry<W>> {
+        self.entries.pop_back()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::history::History;
+    use crate::machine::Machine;
+
+    #[test]
+    fn depth_limits_entries() {
+        let mut machine = Machine::new(&[1101, 1, 1, 9, 1101, 2, 2, 9, 99, 0]);
+
+        machine.enable_history(2);
+        machine.run().unwrap();
+
+        let history = machine.history().unwrap();
+
+        assert_eq!(history.entries().map(|entry| entry.instruction_ptr).collect::<Vec<usize>>(), vec![4, 8]);
+        assert_eq!(History::<i64>::new(0).depth(), 0);
+    }
+
+    #[test]
+    fn step_back_undoes_writes_and_registers() {
+        let program = [109, 7, 21101, 3, 4, 0, 99, 5];
+        let mut machine = Machine::new(&program);
+
+        machine.enable_history(10);
+        machine.run().unwrap();
+
+        assert_eq!(machine.read(7), 7);
+        assert!(machine.step_back().is_some());
+        assert!(!machine.is_halted());
+        assert!(machine.step_back().is_some());
+        assert_eq!(machine.read(7), 5);
+        assert!(machine.step_back().is_some());
+        assert_eq!((machine.instruction_ptr(), machine.relative_base(), machine.steps()), (0, 0, 0));
+        assert_eq!(machine.memory(), &program);
+        assert!(machine.step_back().is_none());
+    }
+
+    #[test]
+    fn step_back_shrinks_grown_memory() {
+        let mut machine = Machine::new(&[1101, 1, 1, 6, 99]);
+
+        machine.enable_history(10);
+        machine.run().unwrap();
+
+        assert_eq!(machine.memory().len(), 7);
+
+        machine.step_back();
+        machine.step_back();
+
+        assert_eq!(machine.memory(), &[1101, 1, 1, 6, 99]);
+    }
+
+    #[test]
+    fn rewound_input_is_consumed_again() {
+        let mut machine = Machine::new(&[3, 0, 4, 0, 99]);
+
+        machine.enable_history(10);
+        machine.push_input(17);
+        machine.run().unwrap();
+
+        while machine.step_back().is_some() {}
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), &[17, 17]);
+    }
+
+    #[test]
+    fn rewind_to_writer_of_self_modified_op_code() {
+        let mut machine = Machine::new(&[1, 8, 0, 4, 1, 0, 0, 0, 98]);
+
+        machine.enable_history(10);
+        machine.run().unwrap();
+
+        let writer = machine.history().unwrap().last_write(4).cloned().unwrap();
+
+        assert_eq!((writer.instruction_ptr, writer.write.unwrap().old_value, writer.write.unwrap().new_value), (0, 1, 99));
+        assert_eq!(machine.rewind_to_last_write(4), Some(writer));
+        assert_eq!(machine.instruction_ptr(), 0);
+        assert_eq!(machine.read(4), 1);
+        assert_eq!(machine.rewind_to_last_write(4), None);
+    }
+}
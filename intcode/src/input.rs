@@ -0,0 +1,61 @@
+use crate::word::Word;
+use advent_of_code_2019::input;
+use std::fs;
+
+pub fn parse_program(program: &str) -> Result<Vec<i64>, String> {
+    parse_words(program)
+}
+
+/// Parses a comma-separated program into words of any size.
+pub fn parse_words<W: Word>(program: &str) -> Result<Vec<W>, String> {
+    program
+        .trim()
+        .split(',')
+        .map(|value| W::parse(value.trim()).map_err(|err| format!("invalid program value: {}: {}", value, err)))
+        .collect()
+}
+
+/// Reads and parses a program file. Errors are prefixed with the path.
+pub fn load_program(path: &str) -> Result<Vec<i64>, String> {
+    fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|text| parse_program(&text))
+        .map_err(|err| format!("{}: {}", path, err))
+}
+
+pub fn program_from_arg_file() -> Vec<i64> {
+    let program = input::single_line_from_arg_file();
+
+    match parse_program(&program) {
+        Ok(program) => program,
+        Err(err) => panic!("unable to parse program: {}", err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bigint::BigInt;
+    use crate::input::{load_program, parse_program, parse_words};
+
+    #[test]
+    fn parse_with_negative_values() {
+        assert_eq!(parse_program("1101,100,-1,4,0\n"), Ok(vec![1101, 100, -1, 4, 0]));
+    }
+
+    #[test]
+    fn parse_words_of_other_sizes() {
+        assert!(parse_words::<i32>("1,3000000000").is_err());
+        assert_eq!(parse_words::<i128>("1,3000000000000000000000"), Ok(vec![1, 3_000_000_000_000_000_000_000]));
+        assert_eq!(parse_words::<BigInt>("-7").unwrap()[0].to_string(), "-7");
+    }
+
+    #[test]
+    fn parse_invalid_value() {
+        assert!(parse_program("1,2,x").is_err());
+    }
+
+    #[test]
+    fn load_missing_program_names_path() {
+        assert!(load_program("no/such/program.txt").unwrap_err().starts_with("no/such/program.txt: "));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::word::Word;
+use std::collections::VecDeque;
+use std::io::{stdin, stdout, Write};
+
+/// Source of values for the INPUT instruction. `Ok(None)` signals that no value is currently available.
+pub trait Input<W = i64> {
+    fn read(&mut self) -> Result<Option<W>, String>;
+}
+
+/// Sink for values produced by the OUTPUT instruction.
+pub trait Output<W = i64> {
+    fn write(&mut self, value: W) -> Result<(), String>;
+}
+
+impl<W, T: Input<W> + ?Sized> Input<W> for Box<T> {
+    fn read(&mut self) -> Result<Option<W>, String> {
+        (**self).read()
+    }
+}
+
+impl<W, T: Output<W> + ?Sized> Output<W> for Box<T> {
+    fn write(&mut self, value: W) -> Result<(), String> {
+        (**self).write(value)
+    }
+}
+
+impl<W, T: Input<W> + ?Sized> Input<W> for &mut T {
+    fn read(&mut self) -> Result<Option<W>, String> {
+        (**self).read()
+    }
+}
+
+impl<W, T: Output<W> + ?Sized> Output<W> for &mut T {
+    fn write(&mut self, value: W) -> Result<(), String> {
+        (**self).write(value)
+    }
+}
+
+impl<W: Word> Input<W> for VecDeque<W> {
+    fn read(&mut self) -> Result<Option<W>, String> {
+        Ok(self.pop_front())
+    }
+}
+
+impl<W: Word> Output<W> for VecDeque<W> {
+    fn write(&mut self, value: W) -> Result<(), String> {
+        self.push_back(value);
+
+        Ok(())
+    }
+}
+
+impl<W: Word> Output<W> for Vec<W> {
+    fn write(&mut self, value: W) -> Result<(), String> {
+        self.push(value);
+
+        Ok(())
+    }
+}
+
+/// Reads one integer per line from stdin, prompting on stdout. End of input is reported as no value available.
+pub struct StdinInput {
+    prompt: Option<String>
+}
+
+impl StdinInput {
+    pub fn new() -> StdinInput {
+        StdinInput { prompt: None }
+    }
+
+    pub fn with_prompt(prompt: &str) -> StdinInput {
+        StdinInput { prompt: Some(prompt.to_string()) }
+    }
+}
+
+impl Default for StdinInput {
+    fn default() -> StdinInput {
+        StdinInput::new()
+    }
+}
+
+impl Input for StdinInput {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        if let Some(prompt) = &self.prompt {
+            print!("{}", prompt);
+
+            stdout().flush().map_err(|err| format!("unable to write to stdout: {}", err))?;
+        }
+
+        let mut line = String::new();
+
+        match stdin().read_line(&mut line) {
+            Ok(0) => Ok(None),
+            Ok(_) => line
+                .trim()
+                .parse::<i64>()
+                .map(Option::Some)
+                .map_err(|err| format!("unable to parse input value: {}: {}", line.trim(), err)),
+            Err(err) => Err(format!("unable to read from stdin: {}", err))
+        }
+    }
+}
+
+/// Prints each value on its own line to stdout.
+#[derive(Default)]
+pub struct StdoutOutput;
+
+impl Output for StdoutOutput {
+    fn write(&mut self, value: i64) -> Result<(), String> {
+        println!("{}", value);
+
+        Ok(())
+    }
+}
+
+/// Adapts any iterator of values into an input.
+pub struct IterInput<I: Iterator<Item = i64>> {
+    iter: I
+}
+
+impl<I: Iterator<Item = i64>> IterInput<I> {
+    pub fn new<T: IntoIterator<IntoIter = I, Item = i64>>(iter: T) -> IterInput<I> {
+        IterInput { iter: iter.into_iter() }
+    }
+}
+
+impl<I: Iterator<Item = i64>> Input for IterInput<I> {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        Ok(self.iter.next())
+    }
+}
+
+/// Adapts a closure into an input.
+pub struct FnInput<F: FnMut() -> Option<i64>> {
+    func: F
+}
+
+impl<F: FnMut() -> Option<i64>> FnInput<F> {
+    pub fn new(func: F) -> FnInput<F> {
+        FnInput { func }
+    }
+}
+
+impl<F: FnMut() -> Option<i64>> Input for FnInput<F> {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        Ok((self.func)())
+    }
+}
+
+/// Adapts a closure into an output.
+pub struct FnOutput<F: FnMut(i64)> {
+    func: F
+}
+
+impl<F: FnMut(i64)> FnOutput<F> {
+    pub fn new(func: F) -> FnOutput<F> {
+        FnOutput { func }
+    }
+}
+
+impl<F: FnMut(i64)> Output for FnOutput<F> {
+    fn write(&mut self, value: i64) -> Result<(), String> {
+        (self.func)(value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::{Input, Output, IterInput, FnInput, FnOutput};
+    use std::collections::VecDeque;
+
+    #[test]
+    fn queue_input_drains_in_order() {
+        let mut input: VecDeque<i64> = vec![1, 2].into();
+
+        assert_eq!(input.read(), Ok(Some(1)));
+        assert_eq!(input.read(), Ok(Some(2)));
+        assert_eq!(input.read(), Ok(None));
+    }
+
+    #[test]
+    fn iter_input_drains_in_order() {
+        let mut input = IterInput::new(5..7);
+
+        assert_eq!(input.read(), Ok(Some(5)));
+        assert_eq!(input.read(), Ok(Some(6)));
+        assert_eq!(input.read(), Ok(None));
+    }
+
+    #[test]
+    fn closures_as_input_and_output() {
+        let mut next = 0;
+        let mut written = Vec::new();
+
+        {
+            let mut input = FnInput::new(|| { next += 1; Some(next) });
+            let mut output = FnOutput::new(|value| written.push(value));
+
+            for _ in 0..3 {
+                let value = input.read().unwrap().unwrap();
+
+                output.write(value * 10).unwrap();
+            }
+        }
+
+        assert_eq!(written, vec![10, 20, 30]);
+    }
+}
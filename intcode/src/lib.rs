@@ -0,0 +1,26 @@
+pub mod input;
+pub mod optype;
+pub mod opcode;
+pub mod error;
+pub mod io;
+pub mod machine;
+pub mod disassembler;
+pub mod assembler;
+pub mod debugger;
+pub mod trace;
+pub mod session;
+pub mod history;
+pub mod snapshot;
+pub mod symbolic;
+pub mod cfg;
+pub mod profile;
+pub mod transpile;
+pub mod word;
+pub mod bigint;
+pub mod network;
+pub mod ascii;
+pub mod fixture;
+
+pub use error::IntcodeError;
+pub use machine::{Machine, RunStatus, WordMachine};
+pub use word::{OverflowPolicy, Word};
@@ -0,0 +1,996 @@
+use crate::error::IntcodeError;
+use crate::history::{History, JournalEntry};
+use crate::opcode::OpCode;
+use crate::optype::{OpType, ParameterMode};
+use crate::io::{Input, Output};
+use crate::snapshot::Snapshot;
+use crate::trace::{MemoryWrite, TraceEvent, Tracer};
+use crate::word::{OverflowPolicy, Word};
+use std::collections::{HashMap, VecDeque};
+
+/// Reason a machine stopped running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RunStatus<W = i64> {
+    NeedsInput,
+    Output(W),
+    Halted
+}
+
+const MAX_READ_PARAMETERS: usize = 2;
+
+/// Limit on the states kept for loop detection; they are forgotten on reaching it.
+const MAX_LOOP_STATES: usize = 1 << 16;
+
+enum Effect<W> {
+    None,
+    Store(W),
+    Jump(usize),
+    Output(W),
+    Blocked,
+    Halt
+}
+
+/// An Intcode machine reading INPUT values from `I` and writing OUTPUT values to `O`. By default both are in-memory
+/// queues, so that inputs can be pushed and outputs inspected directly. Memory holds words of type `W`, `i64` by
+/// default.
+pub struct Machine<I: Input<W> = VecDeque<i64>, O: Output<W> = Vec<i64>, W: Word = i64> {
+    memory: Vec<W>,
+    instruction_ptr: usize,
+    relative_base: i64,
+    halted: bool,
+    steps: u64,
+    input: I,
+    output: O,
+    tracer: Option<Box<dyn Tracer<W>>>,
+    history: Option<History<W>>,
+    rewound_inputs: VecDeque<W>,
+    step_budget: Option<u64>,
+    loop_states: Option<HashMap<(usize, i64), u64>>,
+    decode_cache: bool,
+    decoded: Vec<Option<OpCode>>,
+    overflow_policy: OverflowPolicy,
+    memory_limit: usize
+}
+
+/// A machine with in-memory input and output queues and words of type `W`.
+pub type WordMachine<W> = Machine<VecDeque<W>, Vec<W>, W>;
+
+impl Machine {
+    /// Default limit on the number of words memory may grow to.
+    pub const DEFAULT_MEMORY_LIMIT: usize = 1 << 24;
+
+    pub fn new(program: &[i64]) -> Machine {
+        Machine::with_io(program, VecDeque::new(), Vec::new())
+    }
+}
+
+impl<W: Word> Machine<VecDeque<W>, Vec<W>, W> {
+    /// Creates a machine with words of any size.
+    pub fn from_words(program: &[W]) -> WordMachine<W> {
+        Machine::with_io(program, VecDeque::new(), Vec::new())
+    }
+
+    pub fn from_snapshot(snapshot: &Snapshot<W>) -> WordMachine<W> {
+        let mut machine = Machine::from_words(&[]);
+
+        machine.restore(snapshot);
+
+        machine
+    }
+}
+
+impl<O: Output<W>, W: Word> Machine<VecDeque<W>, O, W> {
+    /// Queues a value to be consumed by a subsequent INPUT instruction.
+    pub fn push_input(&mut self, value: W) {
+        self.input.push_back(value);
+    }
+
+    pub fn snapshot(&self) -> Snapshot<W> {
+        Snapshot {
+            memory: self.memory.clone(),
+            instruction_ptr: self.instruction_ptr,
+            relative_base: self.relative_base,
+            halted: self.halted,
+            steps: self.steps,
+            pending_input: self.rewound_inputs.iter().chain(self.input.iter()).cloned().collect()
+        }
+    }
+
+    /// Replaces the machine's state, including its queued input, with that of a snapshot. Outputs already produced are
+    /// kept, and any journaled history is discarded.
+    pub fn restore(&mut self, snapshot: &Snapshot<W>) {
+        self.memory = snapshot.memory.clone();
+        self.instruction_ptr = snapshot.instruction_ptr;
+        self.relative_base = snapshot.relative_base;
+        self.halted = snapshot.halted;
+        self.steps = snapshot.steps;
+        self.input = snapshot.pending_input.iter().cloned().collect();
+        self.rewound_inputs.clear();
+        self.decoded.clear();
+        self.forget_loop_states();
+
+        if let Some(history) = self.history.as_mut() {
+            *history = History::new(history.depth());
+        }
+    }
+}
+
+impl<I: Input<W>, W: Word> Machine<I, Vec<W>, W> {
+    pub fn outputs(&self) -> &[W] { &self.output }
+}
+
+/// Clones share no state, so each can be run independently. The tracer, if any, is not carried over to the clone.
+impl<I: Input<W> + Clone, O: Output<W> + Clone, W: Word> Clone for Machine<I, O, W> {
+    fn clone(&self) -> Machine<I, O, W> {
+        Machine {
+            memory: self.memory.clone(),
+            instruction_ptr: self.instruction_ptr,
+            relative_base: self.relative_base,
+            halted: self.halted,
+            steps: self.steps,
+            input: self.input.clone(),
+            output: self.output.clone(),
+            tracer: None,
+            history: self.history.clone(),
+            rewound_inputs: self.rewound_inputs.clone(),
+            step_budget: self.step_budget,
+            loop_states: self.loop_states.clone(),
+            decode_cache: self.decode_cache,
+            decoded: self.decoded.clone(),
+            overflow_policy: self.overflow_policy,
+            memory_limit: self.memory_limit
+        }
+    }
+}
+
+impl<I: Input<W>, O: Output<W>, W: Word> Machine<I, O, W> {
+    pub fn with_io(program: &[W], input: I, output: O) -> Machine<I, O, W> {
+        Machine {
+            memory: program.to_vec(),
+            instruction_ptr: 0,
+            relative_base: 0,
+            halted: false,
+            steps: 0,
+            input,
+            output,
+            tracer: None,
+            history: None,
+            rewound_inputs: VecDeque::new(),
+            step_budget: None,
+            loop_states: None,
+            decode_cache: true,
+            decoded: Vec::new(),
+            overflow_policy: OverflowPolicy::default(),
+            memory_limit: Machine::DEFAULT_MEMORY_LIMIT
+        }
+    }
+
+    pub fn memory(&self) -> &[W] { &self.memory }
+    pub fn instruction_ptr(&self) -> usize { self.instruction_ptr }
+    pub fn relative_base(&self) -> i64 { self.relative_base }
+    pub fn is_halted(&self) -> bool { self.halted }
+    pub fn steps(&self) -> u64 { self.steps }
+    pub fn input(&self) -> &I { &self.input }
+    pub fn input_mut(&mut self) -> &mut I { &mut self.input }
+    pub fn output(&self) -> &O { &self.output }
+    pub fn output_mut(&mut self) -> &mut O { &mut self.output }
+
+    /// Attaches a tracer to be notified of every instruction executed from now on, replacing any attached before.
+    pub fn set_tracer(&mut self, tracer: Box<dyn Tracer<W>>) {
+        self.tracer = Some(tracer);
+    }
+
+    pub fn overflow_policy(&self) -> OverflowPolicy { self.overflow_policy }
+
+    /// Selects what ADD and MULTIPLY do with a result that does not fit in a word. By default, overflow is a fault.
+    pub fn set_overflow_policy(&mut self, policy: OverflowPolicy) {
+        self.overflow_policy = policy;
+    }
+
+    pub fn memory_limit(&self) -> usize { self.memory_limit }
+
+    /// Limits the number of words memory may grow to. An instruction writing at or beyond the limit faults, rather than
+    /// growing memory to reach the address. Reads are unaffected: memory beyond its end reads as zero.
+    pub fn set_memory_limit(&mut self, limit: usize) {
+        self.memory_limit = limit;
+    }
+
+    /// Limits the total number of instructions the machine may execute, counting those already executed. Stepping a
+    /// machine that has exhausted its budget is an error.
+    pub fn set_step_budget(&mut self, budget: Option<u64>) {
+        self.step_budget = budget;
+    }
+
+    /// Enables detection of infinite loops. Whenever a jump is taken, the instruction pointer and relative base are
+    /// compared to those after earlier jumps since memory last changed or input or output was performed. As memory is
+    /// unchanged in between, a repeat means the program can never again perform I/O or halt, and is reported as an
+    /// error. Loops that change memory on every pass, or that take more than 65536 jumps to repeat, are not detected.
+    pub fn enable_loop_detection(&mut self) {
+        self.loop_states = Some(HashMap::new());
+    }
+
+    pub fn disable_loop_detection(&mut self) {
+        self.loop_states = None;
+    }
+
+    fn forget_loop_states(&mut self) {
+        if let Some(loop_states) = self.loop_states.as_mut() {
+            loop_states.clear();
+        }
+    }
+
+    fn check_for_loop(&mut self, jumped: bool, performed_io: bool) -> Result<(), IntcodeError> {
+        if performed_io {
+            self.forget_loop_states();
+        } else if jumped {
+            let (instruction_ptr, relative_base, step) = (self.instruction_ptr, self.relative_base, self.steps);
+
+            if let Some(loop_states) = self.loop_states.as_mut() {
+                if loop_states.len() >= MAX_LOOP_STATES {
+                    loop_states.clear();
+                }
+
+                if let Some(first_step) = loop_states.insert((instruction_ptr, relative_base), step) {
+                    return Err(IntcodeError::InfiniteLoop { instruction_ptr, first_step, repeat_step: step });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Enables or disables caching of decoded op codes, which is enabled by default. A cached op code is discarded
+    /// whenever the word it was decoded from is written to.
+    pub fn set_decode_cache(&mut self, enabled: bool) {
+        self.decode_cache = enabled;
+        self.decoded.clear();
+    }
+
+    pub fn take_tracer(&mut self) -> Option<Box<dyn Tracer<W>>> {
+        self.tracer.take()
+    }
+
+    /// Starts journaling executed instructions so that they can be undone, keeping at most `depth` of them. Any
+    /// journal kept so far is discarded.
+    pub fn enable_history(&mut self, depth: usize) {
+        self.history = Some(History::new(depth));
+    }
+
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    pub fn history(&self) -> Option<&History<W>> {
+        self.history.as_ref()
+    }
+
+    /// Undoes the most recently journaled instruction, returning its journal entry, or `None` if there is nothing left
+    /// to undo. Input consumed by the instruction is consumed again, ahead of the machine's input, when it is
+    /// re-executed. Output already written cannot be taken back, and memory changed by `write` is not restored.
+    pub fn step_back(&mut self) -> Option<JournalEntry<W>> {
+        let entry = self.history.as_mut()?.pop()?;
+
+        if let Some(write) = &entry.write {
+            if entry.memory_len <= write.address {
+                self.memory.truncate(entry.memory_len);
+                self.decoded.truncate(entry.memory_len);
+            } else {
+                self.write(write.address, write.old_value.clone());
+            }
+        }
+
+        if let Some(input) = &entry.input {
+            self.rewound_inputs.push_front(input.clone());
+        }
+
+        self.instruction_ptr = entry.instruction_ptr;
+        self.relative_base = entry.relative_base;
+        self.halted = false;
+        self.steps = entry.step;
+        self.forget_loop_states();
+
+        Some(entry)
+    }
+
+    /// Steps back to just before the most recent journaled instruction that wrote to the given address, returning
+    /// that instruction's journal entry. The machine is left unchanged if no such instruction is journaled.
+    pub fn rewind_to_last_write(&mut self, address: usize) -> Option<JournalEntry<W>> {
+        let step = self.history.as_ref()?.last_write(address)?.step;
+
+        loop {
+            let entry = self.step_back()?;
+
+            if entry.step == step {
+                return Some(entry);
+            }
+        }
+    }
+
+    pub fn into_io(self) -> (I, O) {
+        (self.input, self.output)
+    }
+
+    fn check_address(&self, op_code: i64, param_idx: usize, address: &W) -> Result<usize, IntcodeError> {
+        self.check_offset_address(op_code, param_idx, address.to_i64_saturating())
+    }
+
+    fn check_offset_address(&self, op_code: i64, param_idx: usize, address: i64) -> Result<usize, IntcodeError> {
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress { instruction_ptr: self.instruction_ptr, op_code, param_idx, address });
+        }
+
+        Ok(address as usize)
+    }
+
+    /// Reads the value at the given address. Memory beyond the loaded program reads as zero.
+    pub fn read(&self, address: usize) -> W {
+        self.memory.get(address).cloned().unwrap_or_else(W::zero)
+    }
+
+    /// Writes a value at the given address, zero-filling memory up to it if it lies beyond the end of memory. Panics if
+    /// that would grow memory beyond its limit.
+    pub fn write(&mut self, address: usize, value: W) {
+        if address >= self.memory.len() {
+            assert!(address < self.memory_limit, "address {} is beyond the memory limit of {} words", address, self.memory_limit);
+
+            self.memory.resize(address + 1, W::zero());
+            self.forget_loop_states();
+        } else if self.memory[address] != value {
+            self.forget_loop_states();
+        }
+
+        self.memory[address] = value;
+
+        if let Some(decoded) = self.decoded.get_mut(address) {
+            *decoded = None;
+        }
+    }
+
+    fn parameter_value(&self, param_idx: usize) -> W {
+        self.read(self.instruction_ptr + 1 + param_idx)
+    }
+
+    /// Resolves the address a parameter refers to. An immediate parameter refers to its own location.
+    fn resolve_address(&self, op_code: i64, param_mode: ParameterMode, param_idx: usize) -> Result<usize, IntcodeError> {
+        let param_value = self.parameter_value(param_idx);
+
+        match param_mode {
+            ParameterMode::POSITION => self.check_address(op_code, param_idx, &param_value),
+            ParameterMode::RELATIVE =>
+                self.check_offset_address(op_code, param_idx, self.relative_base.saturating_add(param_value.to_i64_saturating())),
+            ParameterMode::IMMEDIATE => Ok(self.instruction_ptr + 1 + param_idx)
+        }
+    }
+
+    fn resolve_read_parameters(&self, op_code_value: i64, op_code: &OpCode) -> Result<[W; MAX_READ_PARAMETERS], IntcodeError> {
+        let mut params = [W::zero(), W::zero()];
+
+        for (i, param_mode) in op_code.read_param_modes().iter().enumerate() {
+            params[i] = self.read(self.resolve_address(op_code_value, *param_mode, i)?);
+        }
+
+        Ok(params)
+    }
+
+    fn resolve_write_address(&self, op_code_value: i64, op_code: &OpCode) -> Result<usize, IntcodeError> {
+        let param_idx = op_code.op_type.parameter_layout().num_read;
+        let address = self.resolve_address(op_code_value, op_code.param_modes()[param_idx], param_idx)?;
+
+        if address >= self.memory.len() && address >= self.memory_limit {
+            return Err(IntcodeError::AddressOutOfRange {
+                instruction_ptr: self.instruction_ptr,
+                op_code: op_code_value,
+                param_idx,
+                address,
+                limit: self.memory_limit
+            });
+        }
+
+        Ok(address)
+    }
+
+    fn arithmetic_result(&self, op_code_value: i64, result: Option<W>) -> Result<Effect<W>, IntcodeError> {
+        match result {
+            Some(value) => Ok(Effect::Store(value)),
+            None => Err(IntcodeError::Overflow { instruction_ptr: self.instruction_ptr, op_code: op_code_value })
+        }
+    }
+
+    fn process_instruction(&mut self, op_code_value: i64, op_code: &OpCode, params: &[W]) -> Result<Effect<W>, IntcodeError> {
+        Ok(match op_code.op_type {
+            OpType::ADD => self.arithmetic_result(op_code_value, params[0].add(&params[1], self.overflow_policy))?,
+            OpType::MULTIPLY => self.arithmetic_result(op_code_value, params[0].multiply(&params[1], self.overflow_policy))?,
+            OpType::INPUT => {
+                let instruction_ptr = self.instruction_ptr;
+
+                if let Some(value) = self.rewound_inputs.pop_front() {
+                    return Ok(Effect::Store(value));
+                }
+
+                match self.input.read() {
+                    Ok(Some(value)) => Effect::Store(value),
+                    Ok(None) => Effect::Blocked,
+                    Err(message) => return Err(IntcodeError::Io { instruction_ptr, op_code: op_code_value, message })
+                }
+            },
+            OpType::OUTPUT => Effect::Output(params[0].clone()),
+            OpType::JUMP_IF_TRUE if !params[0].is_zero() => Effect::Jump(self.check_address(op_code_value, 1, &params[1])?),
+            OpType::JUMP_IF_FALSE if params[0].is_zero() => Effect::Jump(self.check_address(op_code_value, 1, &params[1])?),
+            OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE => Effect::None,
+            OpType::LESS_THAN => Effect::Store(W::from_bool(params[0] < params[1])),
+            OpType::EQUALS => Effect::Store(W::from_bool(params[0] == params[1])),
+            OpType::ADJUST_RELATIVE_BASE => {
+                self.relative_base = self.relative_base.saturating_add(params[0].to_i64_saturating());
+
+                Effect::None
+            },
+            OpType::TERMINATE => Effect::Halt
+        })
+    }
+
+    fn decode(&mut self) -> Result<OpCode, IntcodeError> {
+        let instruction_ptr = self.instruction_ptr;
+
+        if let Some(Some(op_code)) = self.decoded.get(instruction_ptr) {
+            return Ok(*op_code);
+        }
+
+        let op_code_value = self.memory[instruction_ptr].to_i64_saturating();
+        let op_code = OpCode::parse(op_code_value).map_err(|cause| {
+            IntcodeError::InvalidOpCode { instruction_ptr, op_code: op_code_value, cause }
+        })?;
+
+        if self.decode_cache {
+            if self.decoded.len() < self.memory.len() {
+                self.decoded.resize(self.memory.len(), None);
+            }
+
+            self.decoded[instruction_ptr] = Some(op_code);
+        }
+
+        Ok(op_code)
+    }
+
+    fn try_step(&mut self) -> Result<Option<RunStatus<W>>, IntcodeError> {
+        let op_code_value = self.memory[self.instruction_ptr].to_i64_saturating();
+        let op_code = self.decode()?;
+
+        let params = self.resolve_read_parameters(op_code_value, &op_code)?;
+        let write_address =
+            if op_code.op_type.parameter_layout().has_write {
+                Some(self.resolve_write_address(op_code_value, &op_code)?)
+            } else {
+                None
+            };
+
+        let instruction_ptr = self.instruction_ptr;
+        let relative_base = self.relative_base;
+        let memory_len = self.memory.len();
+        let mut next_instruction_ptr = instruction_ptr + op_code.instruction_len();
+        let mut memory_write = None;
+        let mut jumped = false;
+        let mut status = None;
+
+        match self.process_instruction(op_code_value, &op_code, &params)? {
+            Effect::None => (),
+            Effect::Store(value) => {
+                if let Some(address) = write_address {
+                    memory_write = Some(MemoryWrite { address, old_value: self.read(address), new_value: value.clone() });
+
+                    self.write(address, value);
+                }
+            },
+            Effect::Jump(target) => {
+                next_instruction_ptr = target;
+                jumped = true;
+            },
+            Effect::Output(value) => status = Some(RunStatus::Output(value)),
+            Effect::Blocked => return Ok(Some(RunStatus::NeedsInput)),
+            Effect::Halt => {
+                self.halted = true;
+                next_instruction_ptr = instruction_ptr;
+                status = Some(RunStatus::Halted);
+            }
+        }
+
+        let step = self.steps;
+        let input = match (op_code.op_type, &memory_write) { (OpType::INPUT, Some(write)) => Some(write.new_value.clone()), _ => None };
+        let output = match &status { Some(RunStatus::Output(value)) => Some(value.clone()), _ => None };
+        let performed_io = input.is_some() || output.is_some();
+
+        self.instruction_ptr = next_instruction_ptr;
+        self.steps += 1;
+
+        let event = self.tracer.as_ref().map(|_| TraceEvent {
+            step,
+            instruction_ptr,
+            op_code: op_code_value,
+            op_type: op_code.op_type,
+            operands: params[..op_code.read_param_modes().len()].to_vec(),
+            relative_base,
+            write: memory_write.clone(),
+            input: input.clone(),
+            output: output.clone(),
+            next_instruction_ptr
+        });
+
+        if let Some(history) = self.history.as_mut() {
+            history.push(JournalEntry { step, instruction_ptr, relative_base, memory_len, write: memory_write, input, output });
+        }
+
+        if let (Some(tracer), Some(event)) = (self.tracer.as_mut(), event) {
+            tracer.record(&event).map_err(|message| {
+                IntcodeError::Io { instruction_ptr, op_code: op_code_value, message: format!("trace: {}", message) }
+            })?;
+        }
+
+        self.check_for_loop(jumped, performed_io)?;
+
+        Ok(status)
+    }
+
+    /// Executes the instruction at the instruction pointer, returning a status if the instruction produced an output,
+    /// could not proceed for lack of input, or halted the machine. Running off the end of memory halts the machine, as
+    /// does the TERMINATE op code. An INPUT instruction that finds no input available leaves the instruction pointer
+    /// unchanged, so that stepping again once input has been provided resumes execution. A faulting instruction also
+    /// leaves the machine state unchanged, except that a tracer failure or a detected infinite loop is reported only
+    /// once the instruction has taken effect.
+    ///
+    /// Output values are returned to the caller rather than written to the machine's output.
+    pub fn step(&mut self) -> Result<Option<RunStatus<W>>, IntcodeError> {
+        if self.halted || self.instruction_ptr >= self.memory.len() {
+            self.halted = true;
+
+            return Ok(Some(RunStatus::Halted));
+        }
+
+        if let Some(budget) = self.step_budget {
+            if self.steps >= budget {
+                return Err(IntcodeError::StepBudgetExhausted { instruction_ptr: self.instruction_ptr, budget });
+            }
+        }
+
+        self.try_step()
+    }
+
+    /// Runs until the machine produces an output, needs input that isn't available yet, or halts. Execution may be
+    /// resumed by calling this again, after providing input if needed.
+    pub fn run_until_event(&mut self) -> Result<RunStatus<W>, IntcodeError> {
+        loop {
+            if let Some(status) = self.step()? {
+                return Ok(status);
+            }
+        }
+    }
+
+    /// Runs to completion, writing outputs to the machine's output.
+    pub fn run(&mut self) -> Result<(), IntcodeError> {
+        loop {
+            let instruction_ptr = self.instruction_ptr;
+
+            match self.step()? {
+                None => (),
+                Some(RunStatus::Output(value)) => {
+                    if let Err(message) = self.output.write(value) {
+                        return Err(IntcodeError::Io { instruction_ptr, op_code: self.read(instruction_ptr).to_i64_saturating(), message });
+                    }
+                },
+                Some(RunStatus::NeedsInput) => return Err(IntcodeError::InputUnavailable { instruction_ptr }),
+                Some(RunStatus::Halted) => return Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bigint::BigInt;
+    use crate::error::{IntcodeError, OpCodeError};
+    use crate::machine::{Machine, RunStatus, MAX_LOOP_STATES};
+    use crate::io::{IterInput, FnOutput};
+    use crate::word::OverflowPolicy;
+
+    fn test_run(program: &[i64], expected: &[i64]) {
+        let mut machine = Machine::new(program);
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.memory(), expected);
+        assert!(machine.is_halted());
+    }
+
+    #[test]
+    fn immediate_mode_multiply() {
+        test_run(&[1002, 4, 3, 4, 33], &[1002, 4, 3, 4, 99]);
+    }
+
+    #[test]
+    fn negative_immediate_add() {
+        test_run(&[1101, 100, -1, 4, 0], &[1101, 100, -1, 4, 99]);
+    }
+
+    #[test]
+    fn run_off_end_halts() {
+        test_run(&[1, 0, 0, 0], &[2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn less_than_and_equals_store_flags() {
+        test_run(&[1107, 1, 2, 9, 1108, 3, 3, 10, 99, -1, -1], &[1107, 1, 2, 9, 1108, 3, 3, 10, 99, 1, 1]);
+    }
+
+    fn test_io(program: &[i64], inputs: &[i64], expected_outputs: &[i64]) {
+        let mut machine = Machine::new(program);
+
+        for input in inputs {
+            machine.push_input(*input);
+        }
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), expected_outputs);
+    }
+
+    #[test]
+    fn input_echoed_to_output() {
+        test_io(&[3, 0, 4, 0, 99], &[-7], &[-7]);
+    }
+
+    #[test]
+    fn jump_if_true_taken() {
+        test_io(&[1105, 1, 7, 104, 1, 99, 0, 104, 2, 99], &[], &[2]);
+    }
+
+    #[test]
+    fn jump_if_true_not_taken() {
+        test_io(&[1105, 0, 7, 104, 1, 99, 0, 104, 2, 99], &[], &[1]);
+    }
+
+    #[test]
+    fn jump_if_false_taken() {
+        test_io(&[1106, 0, 7, 104, 1, 99, 0, 104, 2, 99], &[], &[2]);
+    }
+
+    #[test]
+    fn jump_if_false_not_taken() {
+        test_io(&[1106, 5, 7, 104, 1, 99, 0, 104, 2, 99], &[], &[1]);
+    }
+
+    #[test]
+    fn iterator_input_and_closure_output() {
+        let mut doubled = Vec::new();
+
+        Machine::with_io(&[3, 9, 1002, 9, 2, 9, 4, 9, 99, 0], IterInput::new(vec![21]), FnOutput::new(|value| doubled.push(value)))
+            .run()
+            .unwrap();
+
+        assert_eq!(doubled, vec![42]);
+    }
+
+    #[test]
+    fn exhausted_input_is_error() {
+        assert_eq!(Machine::new(&[3, 0, 99]).run(), Err(IntcodeError::InputUnavailable { instruction_ptr: 0 }));
+    }
+
+    #[test]
+    fn pause_on_input_starvation_and_resume() {
+        let mut machine = Machine::new(&[3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0]);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(machine.instruction_ptr(), 0);
+
+        machine.push_input(40);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::NeedsInput);
+        assert_eq!(machine.instruction_ptr(), 2);
+
+        machine.push_input(2);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Output(42));
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Halted);
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Halted);
+        assert!(machine.outputs().is_empty());
+    }
+
+    #[test]
+    fn outputs_yielded_in_order() {
+        let mut machine = Machine::new(&[104, 1, 104, 2, 99]);
+
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Output(1));
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Output(2));
+        assert_eq!(machine.run_until_event().unwrap(), RunStatus::Halted);
+    }
+
+    #[test]
+    fn negative_jump_target_is_error() {
+        assert_eq!(Machine::new(&[1101, 0, 0, 7, 1105, 1, -1, 0]).run(), Err(IntcodeError::NegativeAddress {
+            instruction_ptr: 4, op_code: 1105, param_idx: 1, address: -1
+        }));
+    }
+
+    #[test]
+    fn invalid_op_code_is_error() {
+        assert_eq!(Machine::new(&[104, 0, 98]).run(), Err(IntcodeError::InvalidOpCode {
+            instruction_ptr: 2, op_code: 98, cause: OpCodeError::UnknownOpType(98)
+        }));
+    }
+
+    #[test]
+    fn negative_read_address_is_error() {
+        assert_eq!(Machine::new(&[1, 0, -1, 0, 99]).run(), Err(IntcodeError::NegativeAddress {
+            instruction_ptr: 0, op_code: 1, param_idx: 1, address: -1
+        }));
+    }
+
+    #[test]
+    fn negative_relative_write_address_is_error() {
+        assert_eq!(Machine::new(&[109, -5, 21101, 1, 1, 2, 99]).run(), Err(IntcodeError::NegativeAddress {
+            instruction_ptr: 2, op_code: 21101, param_idx: 2, address: -3
+        }));
+    }
+
+    #[test]
+    fn fault_leaves_state_unchanged() {
+        let mut machine = Machine::new(&[1101, 2, 3, 0, 1, 0, -1, 0]);
+
+        assert!(machine.step().unwrap().is_none());
+        assert!(machine.step().is_err());
+        assert_eq!(machine.instruction_ptr(), 4);
+        assert_eq!(machine.memory(), &[5, 2, 3, 0, 1, 0, -1, 0]);
+        assert!(!machine.is_halted());
+    }
+
+    #[test]
+    fn late_fault_leaves_instruction_executed() {
+        struct FailingTracer;
+
+        impl crate::trace::Tracer for FailingTracer {
+            fn record(&mut self, _event: &crate::trace::TraceEvent) -> Result<(), String> {
+                Err("disk full".to_string())
+            }
+        }
+
+        let mut machine = Machine::new(&[1101, 2, 3, 0, 99]);
+
+        machine.set_tracer(Box::new(FailingTracer));
+
+        assert!(matches!(machine.step(), Err(IntcodeError::Io { instruction_ptr: 0, .. })));
+        assert_eq!((machine.instruction_ptr(), machine.steps()), (4, 1));
+        assert_eq!(machine.memory(), &[5, 2, 3, 0, 99]);
+
+        let mut machine = Machine::new(&[1101, 0, 0, 7, 1105, 1, 0, 0]);
+
+        machine.enable_loop_detection();
+
+        assert!(matches!(machine.run(), Err(IntcodeError::InfiniteLoop { instruction_ptr: 0, .. })));
+        assert_eq!(machine.instruction_ptr(), 0);
+    }
+
+    #[test]
+    fn output_failure_is_io_error() {
+        struct FailingOutput;
+
+        impl crate::io::Output for FailingOutput {
+            fn write(&mut self, _value: i64) -> Result<(), String> {
+                Err("disconnected".to_string())
+            }
+        }
+
+        let result = Machine::with_io(&[104, 7, 99], std::collections::VecDeque::new(), FailingOutput).run();
+
+        assert_eq!(result, Err(IntcodeError::Io { instruction_ptr: 0, op_code: 104, message: "disconnected".to_string() }));
+    }
+
+    #[test]
+    fn read_beyond_program_is_zero() {
+        test_run(&[1, 100, 100, 0, 99], &[0, 100, 100, 0, 99]);
+    }
+
+    #[test]
+    fn write_beyond_program_grows_memory() {
+        test_run(&[1101, 3, 4, 7, 99], &[1101, 3, 4, 7, 99, 0, 0, 7]);
+    }
+
+    #[test]
+    fn write_beyond_memory_limit_is_error() {
+        assert_eq!(Machine::new(&[1101, 1, 1, 1_000_000_000_000_000, 99]).run(), Err(IntcodeError::AddressOutOfRange {
+            instruction_ptr: 0,
+            op_code: 1101,
+            param_idx: 2,
+            address: 1_000_000_000_000_000,
+            limit: Machine::DEFAULT_MEMORY_LIMIT
+        }));
+
+        let mut machine = Machine::new(&[1101, 1, 1, 9, 1101, 1, 1, 10, 99]);
+
+        machine.set_memory_limit(10);
+
+        assert!(matches!(machine.run(), Err(IntcodeError::AddressOutOfRange { instruction_ptr: 4, address: 10, limit: 10, .. })));
+        assert_eq!(machine.memory().len(), 10);
+    }
+
+    #[test]
+    fn relative_base_offset() {
+        let mut machine = Machine::new(&[109, 19, 204, -34, 99]);
+
+        machine.relative_base = 2000;
+        machine.write(1985, 42);
+        machine.run().unwrap();
+
+        assert_eq!(machine.relative_base(), 2019);
+        assert_eq!(machine.outputs(), &[42]);
+    }
+
+    #[test]
+    fn relative_mode_write() {
+        test_run(&[109, 5, 21101, 2, 3, 2, 99, 0], &[109, 5, 21101, 2, 3, 2, 99, 5]);
+    }
+
+    #[test]
+    fn supplied_quine() {
+        let quine = [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99];
+
+        test_io(&quine, &[], &quine);
+    }
+
+    #[test]
+    fn supplied_sixteen_digit_output() {
+        test_io(&[1102, 34_915_192, 34_915_192, 7, 4, 7, 99, 0], &[], &[1_219_070_632_396_864]);
+    }
+
+    #[test]
+    fn supplied_large_number_output() {
+        test_io(&[104, 1_125_899_906_842_624, 99], &[], &[1_125_899_906_842_624]);
+    }
+
+    #[test]
+    fn step_budget_stops_run() {
+        let mut machine = Machine::new(&[104, 1, 1105, 1, 0]);
+
+        machine.set_step_budget(Some(5));
+
+        assert_eq!(machine.run(), Err(IntcodeError::StepBudgetExhausted { instruction_ptr: 2, budget: 5 }));
+        assert_eq!(machine.outputs(), &[1, 1, 1]);
+    }
+
+    #[test]
+    fn step_budget_allows_completion() {
+        let mut machine = Machine::new(&[1101, 1, 2, 0, 99]);
+
+        machine.set_step_budget(Some(2));
+
+        assert_eq!(machine.run(), Ok(()));
+    }
+
+    #[test]
+    fn loop_without_io_detected() {
+        let mut machine = Machine::new(&[1105, 1, 0]);
+
+        machine.enable_loop_detection();
+
+        assert_eq!(machine.run(), Err(IntcodeError::InfiniteLoop { instruction_ptr: 0, first_step: 1, repeat_step: 2 }));
+    }
+
+    #[test]
+    fn terminating_loop_not_detected() {
+        let mut machine = Machine::new(&[1001, 8, -1, 8, 1005, 8, 0, 99, 3]);
+
+        machine.enable_loop_detection();
+
+        assert_eq!(machine.run(), Ok(()));
+        assert_eq!(machine.steps(), 7);
+    }
+
+    #[test]
+    fn loop_rewriting_same_value_detected() {
+        let mut machine = Machine::new(&[1101, 0, 5, 9, 109, 0, 1105, 1, 0, 0]);
+
+        machine.enable_loop_detection();
+
+        assert_eq!(machine.run(), Err(IntcodeError::InfiniteLoop { instruction_ptr: 0, first_step: 3, repeat_step: 6 }));
+    }
+
+    #[test]
+    fn loop_changing_memory_not_detected() {
+        // Counts up forever.
+        let mut machine = Machine::new(&[1001, 7, 1, 7, 1105, 1, 0, 0]);
+
+        machine.enable_loop_detection();
+        machine.set_step_budget(Some(1000));
+
+        assert!(matches!(machine.run(), Err(IntcodeError::StepBudgetExhausted { .. })));
+    }
+
+    #[test]
+    fn loop_states_bounded() {
+        // Moves the relative base on every pass, so never repeats.
+        let mut machine = Machine::new(&[109, 1, 1105, 1, 0]);
+
+        machine.enable_loop_detection();
+        machine.set_step_budget(Some(1_000_000));
+
+        assert!(matches!(machine.run(), Err(IntcodeError::StepBudgetExhausted { .. })));
+        assert!(machine.loop_states.as_ref().unwrap().len() <= MAX_LOOP_STATES);
+    }
+
+    #[test]
+    fn loop_with_io_not_detected() {
+        let mut machine = Machine::new(&[104, 1, 1105, 1, 0]);
+
+        machine.enable_loop_detection();
+        machine.set_step_budget(Some(100));
+
+        assert!(matches!(machine.run(), Err(IntcodeError::StepBudgetExhausted { .. })));
+    }
+
+    #[test]
+    fn overwritten_op_code_is_decoded_again() {
+        for decode_cache in [true, false].iter() {
+            let mut machine = Machine::new(&[104, 7, 1101, 0, 99, 0, 1105, 1, 0]);
+
+            machine.set_decode_cache(*decode_cache);
+            machine.set_step_budget(Some(100));
+            machine.run().unwrap();
+
+            assert_eq!(machine.outputs(), &[7]);
+        }
+    }
+
+    #[test]
+    fn stepping_back_restores_decoded_op_code() {
+        let mut machine = Machine::new(&[1101, 0, 99, 4, 104, 5, 99]);
+
+        machine.enable_history(10);
+        machine.run().unwrap();
+        machine.step_back();
+        machine.step_back();
+        machine.write(3, 5);
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), &[99]);
+    }
+
+    #[test]
+    fn overflow_is_error_by_default() {
+        let mut machine = Machine::new(&[1102, i64::MAX, 2, 0, 99]);
+
+        assert_eq!(machine.run(), Err(IntcodeError::Overflow { instruction_ptr: 0, op_code: 1102 }));
+    }
+
+    #[test]
+    fn overflow_policies_for_narrow_words() {
+        let program: [i32; 7] = [1102, 65_536, 65_536, 7, 4, 7, 99];
+
+        let run = |policy: OverflowPolicy| {
+            let mut machine = Machine::from_words(&program);
+
+            machine.set_overflow_policy(policy);
+            machine.run().map(|_| machine.outputs().to_vec())
+        };
+
+        assert_eq!(run(OverflowPolicy::Wrap), Ok(vec![0]));
+        assert_eq!(run(OverflowPolicy::Saturate), Ok(vec![i32::MAX]));
+        assert!(matches!(run(OverflowPolicy::Checked), Err(IntcodeError::Overflow { instruction_ptr: 0, .. })));
+    }
+
+    #[test]
+    fn wide_words_hold_large_products() {
+        let mut machine = Machine::from_words(&[1102, i128::from(i64::MAX), 4, 7, 4, 7, 99, 0]);
+
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), &[i128::from(i64::MAX) * 4]);
+    }
+
+    #[test]
+    fn arbitrary_precision_words() {
+        // Squares the input three times.
+        let program = "3,13,2,13,13,13,2,13,13,13,4,13,99,0".split(',').map(|word| word.parse().unwrap()).collect::<Vec<BigInt>>();
+        let mut machine = Machine::from_words(&program);
+
+        machine.push_input("-1000000000000".parse().unwrap());
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs()[0].to_string(), format!("1{}", "0".repeat(48)));
+    }
+}
@@ -0,0 +1,366 @@
+//! Simulates a network of machines running the same program. Each machine is first given its network address as
+//! input, and from then on reads packets from its own input queue, receiving `NO_PACKET` whenever the queue is empty.
+//! Every three values a machine outputs form a packet: the destination address followed by the values `x` and `y`,
+//! which are queued as input for the destination. Packets sent to `NAT_ADDRESS` are held by the NAT, which keeps only
+//! the most recent; when the network falls idle, the NAT sends the packet it holds to address 0.
+
+use crate::error::IntcodeError;
+use crate::machine::{Machine, RunStatus};
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+
+pub const NAT_ADDRESS: usize = 255;
+
+/// Input value received by a machine reading an empty queue.
+pub const NO_PACKET: i64 = -1;
+
+/// Number of consecutive empty reads after which a machine with an empty queue is considered idle.
+const IDLE_POLLS: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Packet {
+    pub source: usize,
+    pub destination: usize,
+    pub x: i64,
+    pub y: i64
+}
+
+/// How machines take turns. `RoundRobin` runs each machine in turn for up to `quantum` instructions, or until it reads
+/// an empty queue; the network is idle once every queue is empty and every machine has repeatedly read nothing.
+/// `EventDriven` runs a machine only once it has been sent a packet, until it reads an empty queue; the network is idle
+/// once no machine has packets waiting. It suits programs that only act in response to packets.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scheduling {
+    RoundRobin { quantum: u64 },
+    EventDriven
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum NetworkError {
+    Fault { address: usize, error: IntcodeError },
+    Halted { address: usize },
+    UnknownDestination { source: usize, destination: i64 },
+    /// The network fell idle before any packet was sent to the NAT.
+    Idle
+}
+
+impl Display for NetworkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NetworkError::Fault { address, error } => write!(f, "machine {} faulted: {}", address, error),
+            NetworkError::Halted { address } => write!(f, "machine {} halted", address),
+            NetworkError::UnknownDestination { source, destination } =>
+                write!(f, "machine {} sent a packet to unknown address: {}", source, destination),
+            NetworkError::Idle => write!(f, "network idle with no packet held by the NAT")
+        }
+    }
+}
+
+impl std::error::Error for NetworkError {}
+
+struct Node {
+    machine: Machine,
+    pending_output: Vec<i64>,
+    empty_polls: u32
+}
+
+pub struct Network {
+    nodes: Vec<Node>,
+    scheduling: Scheduling,
+    nat: Option<Packet>,
+    sent: VecDeque<Packet>,
+    next_turn: usize,
+    ready: VecDeque<usize>
+}
+
+impl Network {
+    /// Boots `size` machines, giving each its address.
+    pub fn new(program: &[i64], size: usize, scheduling: Scheduling) -> Network {
+        let nodes = (0..size).map(|address| {
+            let mut machine = Machine::new(program);
+
+            machine.push_input(address as i64);
+
+            Node { machine, pending_output: Vec::with_capacity(3), empty_polls: 0 }
+        }).collect();
+
+        Network { nodes, scheduling, nat: None, sent: VecDeque::new(), next_turn: 0, ready: (0..size).collect() }
+    }
+
+    pub fn size(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The packet the NAT will send once the network is idle.
+    pub fn nat_packet(&self) -> Option<Packet> {
+        self.nat
+    }
+
+    /// Runs the network until the next packet is sent, whether by a machine or by the NAT.
+    pub fn next_packet(&mut self) -> Result<Packet, NetworkError> {
+        loop {
+            if let Some(packet) = self.sent.pop_front() {
+                return Ok(packet);
+            }
+
+            match self.scheduling {
+                Scheduling::RoundRobin { quantum } => {
+                    let address = self.next_turn;
+
+                    self.next_turn = (self.next_turn + 1) % self.nodes.len().max(1);
+                    if address < self.nodes.len() {
+                        self.run_node(address, Some(quantum.max(1)))?;
+                    }
+
+                    if self.is_idle() {
+                        self.wake_from_nat()?;
+                    }
+                },
+                Scheduling::EventDriven => match self.ready.pop_front() {
+                    Some(address) => self.run_node(address, None)?,
+                    None => self.wake_from_nat()?
+                }
+            }
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.nodes.iter().all(|node| node.empty_polls >= IDLE_POLLS && node.machine.input().is_empty())
+    }
+
+    /// Runs a machine for at most `max_steps` instructions, until it reads an empty queue.
+    fn run_node(&mut self, address: usize, max_steps: Option<u64>) -> Result<(), NetworkError> {
+        let mut steps = 0;
+
+        while max_steps.is_none_or(|max_steps| steps < max_steps) {
+            let node = &mut self.nodes[address];
+            let fault = |error| NetworkError::Fault { address, error };
+
+            match node.machine.step().map_err(fault)? {
+                None => (),
+                Some(RunStatus::NeedsInput) => {
+                    node.empty_polls += 1;
+                    node.machine.push_input(NO_PACKET);
+                    node.machine.step().map_err(fault)?;
+
+                    return Ok(());
+                },
+                Some(RunStatus::Output(value)) => {
+                    node.empty_polls = 0;
+                    node.pending_output.push(value);
+
+                    if node.pending_output.len() == 3 {
+                        let (destination, x, y) = (node.pending_output[0], node.pending_output[1], node.pending_output[2]);
+
+                        node.pending_output.clear();
+                        self.route(address, destination, x, y)?;
+                    }
+                },
+                Some(RunStatus::Halted) => return Err(NetworkError::Halted { address })
+            }
+
+            steps += 1;
+        }
+
+        Ok(())
+    }
+
+    fn route(&mut self, source: usize, destination: i64, x: i64, y: i64) -> Result<(), NetworkError> {
+        let packet = match destination {
+            destination if destination == NAT_ADDRESS as i64 => {
+                let packet = Packet { source, destination: NAT_ADDRESS, x, y };
+
+                self.nat = Some(packet);
+
+                packet
+            },
+            destination if 0 <= destination && (destination as usize) < self.nodes.len() => {
+                let packet = Packet { source, destination: destination as usize, x, y };
+
+                self.deliver(packet);
+
+                packet
+            },
+            _ => return Err(NetworkError::UnknownDestination { source, destination })
+        };
+
+        self.sent.push_back(packet);
+
+        Ok(())
+    }
+
+    fn deliver(&mut self, packet: Packet) {
+        let node = &mut self.nodes[packet.destination];
+
+        node.machine.push_input(packet.x);
+        node.machine.push_input(packet.y);
+        node.empty_polls = 0;
+
+        if !self.ready.contains(&packet.destination) {
+            self.ready.push_back(packet.destination);
+        }
+    }
+
+    fn wake_from_nat(&mut self) -> Result<(), NetworkError> {
+        let nat = self.nat.ok_or(NetworkError::Idle)?;
+        let packet = Packet { source: NAT_ADDRESS, destination: 0, x: nat.x, y: nat.y };
+
+        if self.nodes.is_empty() {
+            return Err(NetworkError::Idle);
+        }
+
+        self.deliver(packet);
+        self.sent.push_back(packet);
+
+        Ok(())
+    }
+}
+
+/// The first packet any machine sends to the NAT.
+pub fn first_packet_to_nat(network: &mut Network) -> Result<Packet, NetworkError> {
+    loop {
+        let packet = network.next_packet()?;
+
+        if packet.destination == NAT_ADDRESS {
+            return Ok(packet);
+        }
+    }
+}
+
+/// The first `y` value the NAT sends to address 0 twice in a row.
+pub fn first_repeated_nat_y(network: &mut Network) -> Result<i64, NetworkError> {
+    let mut last_y = None;
+
+    loop {
+        let packet = network.next_packet()?;
+
+        if packet.source == NAT_ADDRESS {
+            if last_y == Some(packet.y) {
+                return Ok(packet.y);
+            }
+
+            last_y = Some(packet.y);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::assembler::assemble;
+    use crate::network::{first_packet_to_nat, first_repeated_nat_y, Network, NetworkError, Packet, Scheduling, NAT_ADDRESS};
+
+    const SCHEDULINGS: [Scheduling; 3] = [
+        Scheduling::RoundRobin { quantum: 1 },
+        Scheduling::RoundRobin { quantum: 1000 },
+        Scheduling::EventDriven
+    ];
+
+    // Machine 0 starts a packet that each machine passes to the next, counting hops in x and doubling y, until the
+    // third hop sends it to the NAT.
+    fn relay() -> Vec<i64> {
+        assemble(concat!(
+            "        IN   [addr]\n",
+            "        JT   [addr], #poll\n",
+            "        OUT  #1\n",
+            "        OUT  #1\n",
+            "        OUT  #5\n",
+            "poll:   IN   [x]\n",
+            "        EQ   [x], #-1, [flag]\n",
+            "        JT   [flag], #poll\n",
+            "        IN   [y]\n",
+            "        EQ   [x], #3, [flag]\n",
+            "        JT   [flag], #nat\n",
+            "        ADD  [addr], #1, [dest]\n",
+            "        OUT  [dest]\n",
+            "        ADD  [x], #1, [x]\n",
+            "        OUT  [x]\n",
+            "        MUL  [y], #2, [y]\n",
+            "        OUT  [y]\n",
+            "        JT   #1, #poll\n",
+            "nat:    OUT  #255\n",
+            "        OUT  [x]\n",
+            "        OUT  [y]\n",
+            "        JT   #1, #poll\n",
+            "addr:   data 0\n",
+            "x:      data 0\n",
+            "y:      data 0\n",
+            "dest:   data 0\n",
+            "flag:   data 0\n"
+        )).unwrap()
+    }
+
+    // Sends the machine's address to the NAT once, then polls forever.
+    fn report_address() -> Vec<i64> {
+        assemble(concat!(
+            "        IN   [addr]\n",
+            "        OUT  #255\n",
+            "        OUT  [addr]\n",
+            "        OUT  [addr]\n",
+            "poll:   IN   [addr]\n",
+            "        JT   #1, #poll\n",
+            "addr:   data 0\n"
+        )).unwrap()
+    }
+
+    #[test]
+    fn packets_routed_between_machines() {
+        for scheduling in &SCHEDULINGS {
+            let mut network = Network::new(&relay(), 50, *scheduling);
+            let packets = (0..6).map(|_| network.next_packet().unwrap()).collect::<Vec<Packet>>();
+
+            assert_eq!(packets, vec![
+                Packet { source: 0, destination: 1, x: 1, y: 5 },
+                Packet { source: 1, destination: 2, x: 2, y: 10 },
+                Packet { source: 2, destination: 3, x: 3, y: 20 },
+                Packet { source: 3, destination: NAT_ADDRESS, x: 3, y: 20 },
+                Packet { source: NAT_ADDRESS, destination: 0, x: 3, y: 20 },
+                Packet { source: 0, destination: NAT_ADDRESS, x: 3, y: 20 }
+            ], "{:?}", scheduling);
+        }
+    }
+
+    #[test]
+    fn nat_repeats_after_idle() {
+        for scheduling in &SCHEDULINGS {
+            let mut network = Network::new(&relay(), 50, *scheduling);
+
+            assert_eq!(first_packet_to_nat(&mut network).map(|packet| packet.y), Ok(20));
+            assert_eq!(first_repeated_nat_y(&mut network), Ok(20));
+        }
+    }
+
+    #[test]
+    fn nat_keeps_latest_packet() {
+        for scheduling in &SCHEDULINGS {
+            let mut network = Network::new(&report_address(), 50, *scheduling);
+
+            for _ in 0..50 {
+                network.next_packet().unwrap();
+            }
+
+            assert_eq!(network.nat_packet(), Some(Packet { source: 49, destination: NAT_ADDRESS, x: 49, y: 49 }));
+            assert_eq!(network.next_packet(), Ok(Packet { source: NAT_ADDRESS, destination: 0, x: 49, y: 49 }));
+        }
+    }
+
+    #[test]
+    fn idle_without_nat_packet_is_error() {
+        for scheduling in &SCHEDULINGS {
+            assert_eq!(Network::new(&[3, 5, 1105, 1, 0, 0], 3, *scheduling).next_packet(), Err(NetworkError::Idle));
+        }
+    }
+
+    #[test]
+    fn unknown_destination_is_error() {
+        let mut network = Network::new(&[104, 7, 104, 0, 104, 0, 99], 2, Scheduling::EventDriven);
+
+        assert_eq!(network.next_packet(), Err(NetworkError::UnknownDestination { source: 0, destination: 7 }));
+    }
+
+    #[test]
+    fn halted_machine_is_error() {
+        let mut network = Network::new(&[3, 0, 99], 2, Scheduling::RoundRobin { quantum: 5 });
+
+        assert_eq!(network.next_packet(), Err(NetworkError::Halted { address: 0 }));
+    }
+}
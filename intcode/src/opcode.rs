@@ -0,0 +1,123 @@
+use crate::error::OpCodeError;
+use crate::optype::{OpType, ParameterMode};
+
+/// A decoded op code. Decoding performs no allocation, so that op codes can be decoded or copied freely on every step.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OpCode {
+    pub op_type: OpType,
+    modes: [ParameterMode; OpCode::MAX_PARAMETERS]
+}
+
+impl OpCode {
+    pub const MAX_PARAMETERS: usize = 3;
+
+    const DIGIT_BASE: i64 = 10;
+    const OP_TYPE_BASE: i64 = 100;
+
+    fn num_digits(mut value: i64) -> usize {
+        let mut num_digits = 0;
+
+        while value > 0 {
+            num_digits += 1;
+            value /= OpCode::DIGIT_BASE;
+        }
+
+        num_digits
+    }
+
+    pub fn parse(value: i64) -> Result<OpCode, OpCodeError> {
+        if value < 0 {
+            return Err(OpCodeError::Negative);
+        }
+
+        let op_type_value = value % OpCode::OP_TYPE_BASE;
+        let op_type = OpType::parse(op_type_value).ok_or(OpCodeError::UnknownOpType(op_type_value))?;
+        let num_params_expected = op_type.num_parameters();
+
+        let mut mode_digits = value / OpCode::OP_TYPE_BASE;
+        let num_params_found = OpCode::num_digits(mode_digits);
+
+        if num_params_found > num_params_expected {
+            return Err(OpCodeError::ExcessParameterModes { found: num_params_found, expected: num_params_expected });
+        }
+
+        let mut modes = [ParameterMode::POSITION; OpCode::MAX_PARAMETERS];
+
+        for (idx, mode) in modes.iter_mut().enumerate().take(num_params_expected) {
+            let digit = mode_digits % OpCode::DIGIT_BASE;
+
+            *mode = ParameterMode::parse(digit).ok_or(OpCodeError::InvalidParameterMode { param_idx: idx, mode: digit })?;
+            mode_digits /= OpCode::DIGIT_BASE;
+        }
+
+        if op_type.parameter_layout().has_write {
+            if let ParameterMode::IMMEDIATE = modes[num_params_expected - 1] {
+                return Err(OpCodeError::ImmediateWrite { param_idx: num_params_expected - 1 });
+            }
+        }
+
+        Ok(OpCode { op_type, modes })
+    }
+
+    /// Modes of all parameters, the write parameter included.
+    pub fn param_modes(&self) -> &[ParameterMode] {
+        &self.modes[..self.op_type.num_parameters()]
+    }
+
+    pub fn read_param_modes(&self) -> &[ParameterMode] {
+        &self.modes[..self.op_type.parameter_layout().num_read]
+    }
+
+    pub fn instruction_len(&self) -> usize {
+        self.op_type.num_parameters() + 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::OpCodeError;
+    use crate::opcode::OpCode;
+    use crate::optype::{OpType, ParameterMode};
+
+    fn test_parse(value: i64, expected_type: OpType, expected_modes: &[ParameterMode]) {
+        let op_code = OpCode::parse(value).unwrap();
+
+        assert_eq!(op_code.op_type, expected_type);
+        assert_eq!(op_code.param_modes(), expected_modes);
+    }
+
+    #[test]
+    fn parse_without_modes() {
+        test_parse(2, OpType::MULTIPLY, &[ParameterMode::POSITION, ParameterMode::POSITION, ParameterMode::POSITION]);
+    }
+
+    #[test]
+    fn parse_with_implied_leading_zero_modes() {
+        test_parse(1002, OpType::MULTIPLY, &[ParameterMode::POSITION, ParameterMode::IMMEDIATE, ParameterMode::POSITION]);
+    }
+
+    #[test]
+    fn parse_terminate() {
+        test_parse(99, OpType::TERMINATE, &[]);
+    }
+
+    #[test]
+    fn parse_immediate_write_is_rejected() {
+        assert_eq!(OpCode::parse(10001).err(), Some(OpCodeError::ImmediateWrite { param_idx: 2 }));
+    }
+
+    #[test]
+    fn parse_excess_modes_is_rejected() {
+        assert_eq!(OpCode::parse(11104).err(), Some(OpCodeError::ExcessParameterModes { found: 3, expected: 1 }));
+    }
+
+    #[test]
+    fn parse_invalid_mode_is_rejected() {
+        assert_eq!(OpCode::parse(3101).err(), Some(OpCodeError::InvalidParameterMode { param_idx: 1, mode: 3 }));
+    }
+
+    #[test]
+    fn parse_unknown_op_type_is_rejected() {
+        assert_eq!(OpCode::parse(98).err(), Some(OpCodeError::UnknownOpType(98)));
+    }
+}
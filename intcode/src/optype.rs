@@ -0,0 +1,120 @@
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ParameterMode {
+    POSITION,
+    IMMEDIATE,
+    RELATIVE
+}
+
+impl ParameterMode {
+    pub fn parse(mode: i64) -> Option<ParameterMode> {
+        match mode {
+            0 => Some(ParameterMode::POSITION),
+            1 => Some(ParameterMode::IMMEDIATE),
+            2 => Some(ParameterMode::RELATIVE),
+            _ => None
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        match self {
+            ParameterMode::POSITION => 0,
+            ParameterMode::IMMEDIATE => 1,
+            ParameterMode::RELATIVE => 2
+        }
+    }
+}
+
+pub struct ParameterLayout {
+    pub num_read: usize,
+    pub has_write: bool
+}
+
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OpType {
+    ADD,
+    MULTIPLY,
+    INPUT,
+    OUTPUT,
+    JUMP_IF_TRUE,
+    JUMP_IF_FALSE,
+    LESS_THAN,
+    EQUALS,
+    ADJUST_RELATIVE_BASE,
+    TERMINATE
+}
+
+impl OpType {
+    pub const ALL: [OpType; 10] = [
+        OpType::ADD, OpType::MULTIPLY, OpType::INPUT, OpType::OUTPUT, OpType::JUMP_IF_TRUE, OpType::JUMP_IF_FALSE,
+        OpType::LESS_THAN, OpType::EQUALS, OpType::ADJUST_RELATIVE_BASE, OpType::TERMINATE
+    ];
+
+    pub fn parse(op_code: i64) -> Option<OpType> {
+        match op_code {
+            1 => Some(OpType::ADD),
+            2 => Some(OpType::MULTIPLY),
+            3 => Some(OpType::INPUT),
+            4 => Some(OpType::OUTPUT),
+            5 => Some(OpType::JUMP_IF_TRUE),
+            6 => Some(OpType::JUMP_IF_FALSE),
+            7 => Some(OpType::LESS_THAN),
+            8 => Some(OpType::EQUALS),
+            9 => Some(OpType::ADJUST_RELATIVE_BASE),
+            99 => Some(OpType::TERMINATE),
+            _ => None
+        }
+    }
+
+    pub fn parameter_layout(&self) -> &ParameterLayout {
+        match self {
+            OpType::ADD | OpType::MULTIPLY | OpType::LESS_THAN | OpType::EQUALS =>
+                &ParameterLayout { num_read: 2, has_write: true },
+            OpType::INPUT => &ParameterLayout { num_read: 0, has_write: true },
+            OpType::OUTPUT | OpType::ADJUST_RELATIVE_BASE => &ParameterLayout { num_read: 1, has_write: false },
+            OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE => &ParameterLayout { num_read: 2, has_write: false },
+            OpType::TERMINATE => &ParameterLayout { num_read: 0, has_write: false }
+        }
+    }
+
+    pub fn value(&self) -> i64 {
+        match self {
+            OpType::ADD => 1,
+            OpType::MULTIPLY => 2,
+            OpType::INPUT => 3,
+            OpType::OUTPUT => 4,
+            OpType::JUMP_IF_TRUE => 5,
+            OpType::JUMP_IF_FALSE => 6,
+            OpType::LESS_THAN => 7,
+            OpType::EQUALS => 8,
+            OpType::ADJUST_RELATIVE_BASE => 9,
+            OpType::TERMINATE => 99
+        }
+    }
+
+    pub fn from_mnemonic(mnemonic: &str) -> Option<OpType> {
+        OpType::ALL.iter().find(|op_type| op_type.mnemonic().eq_ignore_ascii_case(mnemonic)).copied()
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            OpType::ADD => "ADD",
+            OpType::MULTIPLY => "MUL",
+            OpType::INPUT => "IN",
+            OpType::OUTPUT => "OUT",
+            OpType::JUMP_IF_TRUE => "JT",
+            OpType::JUMP_IF_FALSE => "JF",
+            OpType::LESS_THAN => "LT",
+            OpType::EQUALS => "EQ",
+            OpType::ADJUST_RELATIVE_BASE => "RBO",
+            OpType::TERMINATE => "HLT"
+        }
+    }
+
+    pub fn num_parameters(&self) -> usize {
+        let layout = self.parameter_layout();
+
+        layout.num_read + if layout.has_write { 1 } else { 0 }
+    }
+}
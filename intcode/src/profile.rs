@@ -0,0 +1,291 @@
+//! Execution profiling. A `Profiler` attached to a machine as its tracer counts executions per address and per op
+//! type, and accumulates the wall-clock time between consecutive instructions against the op type of the later one.
+//! Time spent in inputs and outputs wrapped by the profiler is left out of that and reported separately as I/O wait.
+
+use crate::cfg::ControlFlowGraph;
+use crate::io::{Input, Output};
+use crate::optype::OpType;
+use crate::trace::{TraceEvent, Tracer};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OpTypeProfile {
+    pub op_type: OpType,
+    pub count: u64,
+    pub time: Duration
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProfileReport {
+    pub steps: u64,
+    pub time: Duration,
+    pub io_wait: Duration,
+    pub address_counts: BTreeMap<usize, u64>,
+    pub op_types: Vec<OpTypeProfile>,
+    /// Addresses of statically reachable instructions that never executed.
+    pub uncovered: Vec<usize>
+}
+
+struct Counts {
+    last_event: Instant,
+    pending_io_wait: Duration,
+    io_wait: Duration,
+    address_counts: BTreeMap<usize, u64>,
+    op_types: Vec<OpTypeProfile>
+}
+
+/// Clones share the same counts, so a clone can be attached to a machine and the report taken from the original.
+#[derive(Clone)]
+pub struct Profiler {
+    counts: Rc<RefCell<Counts>>
+}
+
+impl Profiler {
+    /// Creates a profiler. The first instruction's time is measured from now, so attach it just before running.
+    pub fn new() -> Profiler {
+        Profiler {
+            counts: Rc::new(RefCell::new(Counts {
+                last_event: Instant::now(),
+                pending_io_wait: Duration::default(),
+                io_wait: Duration::default(),
+                address_counts: BTreeMap::new(),
+                op_types: OpType::ALL.iter()
+                    .map(|op_type| OpTypeProfile { op_type: *op_type, count: 0, time: Duration::default() })
+                    .collect()
+            }))
+        }
+    }
+
+    /// Summarises the instructions executed so far, finding uncovered instructions in the given program.
+    pub fn report(&self, program: &[i64]) -> ProfileReport {
+        let counts = self.counts.borrow();
+        let uncovered = ControlFlowGraph::build(program).blocks.iter()
+            .flat_map(|block| block.lines.iter().map(|line| line.address()))
+            .filter(|address| !counts.address_counts.contains_key(address))
+            .collect();
+
+        ProfileReport {
+            steps: counts.op_types.iter().map(|profile| profile.count).sum(),
+            time: counts.op_types.iter().map(|profile| profile.time).sum(),
+            io_wait: counts.io_wait,
+            address_counts: counts.address_counts.clone(),
+            op_types: counts.op_types.iter().filter(|profile| profile.count > 0).copied().collect(),
+            uncovered
+        }
+    }
+
+    /// Wraps an input so that the time spent waiting on it is reported as I/O wait rather than instruction time.
+    pub fn input<I: Input>(&self, inner: I) -> ProfiledInput<I> {
+        ProfiledInput { inner, profiler: self.clone() }
+    }
+
+    /// Wraps an output so that the time spent writing to it is reported as I/O wait rather than instruction time.
+    pub fn output<O: Output>(&self, inner: O) -> ProfiledOutput<O> {
+        ProfiledOutput { inner, profiler: self.clone() }
+    }
+
+    fn time_io<T>(&self, io: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = io();
+
+        self.counts.borrow_mut().pending_io_wait += start.elapsed();
+
+        result
+    }
+}
+
+impl Default for Profiler {
+    fn default() -> Profiler {
+        Profiler::new()
+    }
+}
+
+impl<W> Tracer<W> for Profiler {
+    fn record(&mut self, event: &TraceEvent<W>) -> Result<(), String> {
+        let mut counts = self.counts.borrow_mut();
+        let io_wait = std::mem::take(&mut counts.pending_io_wait);
+        let elapsed = counts.last_event.elapsed().saturating_sub(io_wait);
+
+        counts.io_wait += io_wait;
+        *counts.address_counts.entry(event.instruction_ptr).or_insert(0) += 1;
+
+        if let Some(profile) = counts.op_types.iter_mut().find(|profile| profile.op_type == event.op_type) {
+            profile.count += 1;
+            profile.time += elapsed;
+        }
+
+        counts.last_event = Instant::now();
+
+        Ok(())
+    }
+}
+
+pub struct ProfiledInput<I: Input> {
+    inner: I,
+    profiler: Profiler
+}
+
+impl<I: Input> Input for ProfiledInput<I> {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        let inner = &mut self.inner;
+
+        self.profiler.time_io(|| inner.read())
+    }
+}
+
+pub struct ProfiledOutput<O: Output> {
+    inner: O,
+    profiler: Profiler
+}
+
+impl<O: Output> Output for ProfiledOutput<O> {
+    fn write(&mut self, value: i64) -> Result<(), String> {
+        let inner = &mut self.inner;
+
+        self.profiler.time_io(|| inner.write(value))
+    }
+}
+
+impl ProfileReport {
+    /// The most frequently executed addresses, most frequent first, breaking ties by address.
+    pub fn hot_spots(&self, limit: usize) -> Vec<(usize, u64)> {
+        let mut hot_spots = self.address_counts.iter().map(|(address, count)| (*address, *count)).collect::<Vec<(usize, u64)>>();
+
+        hot_spots.sort_by(|(left_address, left_count), (right_address, right_count)| {
+            right_count.cmp(left_count).then(left_address.cmp(right_address))
+        });
+        hot_spots.truncate(limit);
+
+        hot_spots
+    }
+
+    pub fn to_text(&self) -> String {
+        let summary = format!("{} instructions executed in {:?}, plus {:?} waiting for i/o", self.steps, self.time, self.io_wait);
+        let mut lines = vec![summary, String::new(),
+                             "op     count        time".to_string()];
+
+        for profile in &self.op_types {
+            lines.push(format!("{:<4} {:>7} {:>11?}", profile.op_type.mnemonic(), profile.count, profile.time));
+        }
+
+        lines.push(String::new());
+        lines.push("hot spots:".to_string());
+
+        for (address, count) in self.hot_spots(10) {
+            lines.push(format!("{:>6}: {}", address, count));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("uncovered instructions: {}", self.uncovered.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ")));
+
+        lines.join("\n") + "\n"
+    }
+
+    /// Formats the report as a JSON object. Times are in nanoseconds.
+    pub fn to_json(&self) -> String {
+        let address_counts = self.address_counts.iter()
+            .map(|(address, count)| format!("{{\"address\":{},\"count\":{}}}", address, count))
+            .collect::<Vec<String>>()
+            .join(",");
+        let op_types = self.op_types.iter()
+            .map(|profile| format!("{{\"op\":\"{}\",\"count\":{},\"time_ns\":{}}}",
+                                   profile.op_type.mnemonic(), profile.count, profile.time.as_nanos()))
+            .collect::<Vec<String>>()
+            .join(",");
+        let uncovered = self.uncovered.iter().map(ToString::to_string).collect::<Vec<String>>().join(",");
+
+        format!("{{\"steps\":{},\"time_ns\":{},\"io_wait_ns\":{},\"op_types\":[{}],\"addresses\":[{}],\
+                 \"uncovered\":[{}]}}",
+                self.steps, self.time.as_nanos(), self.io_wait.as_nanos(), op_types, address_counts, uncovered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::FnInput;
+    use crate::machine::Machine;
+    use crate::optype::OpType;
+    use crate::profile::{ProfileReport, Profiler};
+    use std::thread;
+    use std::time::Duration;
+
+    // Echoes the input, taking a separate path when it is zero.
+    const ECHO: [i64; 13] = [3, 12, 1005, 12, 9, 1101, 0, 0, 12, 4, 12, 99, 1];
+
+    fn profile(program: &[i64], input: i64) -> ProfileReport {
+        let profiler = Profiler::new();
+        let mut machine = Machine::new(program);
+
+        machine.push_input(input);
+        machine.set_tracer(Box::new(profiler.clone()));
+        machine.run().unwrap();
+
+        profiler.report(program)
+    }
+
+    #[test]
+    fn counts_per_address_and_op_type() {
+        let report = profile(&ECHO, 0);
+
+        assert_eq!(report.steps, 5);
+        assert_eq!(report.address_counts.keys().copied().collect::<Vec<usize>>(), vec![0, 2, 5, 9, 11]);
+        assert_eq!(report.op_types.iter().map(|profile| (profile.op_type, profile.count)).collect::<Vec<(OpType, u64)>>(), vec![
+            (OpType::ADD, 1), (OpType::INPUT, 1), (OpType::OUTPUT, 1), (OpType::JUMP_IF_TRUE, 1), (OpType::TERMINATE, 1)
+        ]);
+    }
+
+    #[test]
+    fn uncovered_branch_reported() {
+        assert_eq!(profile(&ECHO, 0).uncovered, Vec::<usize>::new());
+        assert_eq!(profile(&ECHO, 3).uncovered, vec![5]);
+    }
+
+    #[test]
+    fn hot_spots_in_loop() {
+        // Counts the input down to zero.
+        let report = profile(&[3, 10, 1001, 10, -1, 10, 1005, 10, 2, 99, 0], 3);
+
+        assert_eq!(report.hot_spots(2), vec![(2, 3), (6, 3)]);
+    }
+
+    #[test]
+    fn io_wait_reported_separately() {
+        let profiler = Profiler::new();
+        let input = profiler.input(FnInput::new(|| {
+            thread::sleep(Duration::from_millis(50));
+
+            Some(3)
+        }));
+        let mut machine = Machine::with_io(&ECHO, input, profiler.output(Vec::new()));
+
+        machine.set_tracer(Box::new(profiler.clone()));
+        machine.run().unwrap();
+
+        let report = profiler.report(&ECHO);
+        let input_time = report.op_types.iter().find(|profile| profile.op_type == OpType::INPUT).unwrap().time;
+
+        assert!(report.io_wait >= Duration::from_millis(50));
+        assert!(input_time < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn json_report() {
+        let json = profile(&ECHO, 3).to_json();
+
+        assert!(json.starts_with("{\"steps\":4,\"time_ns\":"));
+        assert!(json.contains("{\"op\":\"JT\",\"count\":1,\"time_ns\":"));
+        assert!(json.ends_with("\"addresses\":[{\"address\":0,\"count\":1},{\"address\":2,\"count\":1},\
+                                {\"address\":9,\"count\":1},{\"address\":11,\"count\":1}],\"uncovered\":[5]}"));
+    }
+
+    #[test]
+    fn text_report() {
+        let text = profile(&ECHO, 3).to_text();
+
+        assert!(text.starts_with("4 instructions executed in "));
+        assert!(text.ends_with("uncovered instructions: 5\n"));
+    }
+}
@@ -0,0 +1,382 @@
+//! Recording and replay of the values a program consumes and produces. Sessions are saved as text: a version header
+//! followed by one event per line, in the order they happened:
+//!
+//! ```text
+//! intcode-session 1
+//! in 5
+//! out 999
+//! end halted
+//! ```
+//!
+//! The final `end` line records how the run finished: `halted`, `input` if it stopped because its input ran out, or
+//! `fault` followed by the error message.
+
+use crate::io::{Input, Output};
+use crate::machine::{Machine, RunStatus};
+use crate::IntcodeError;
+use std::cell::RefCell;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::rc::Rc;
+
+const HEADER: &str = "intcode-session 1";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SessionEvent {
+    Input(i64),
+    Output(i64)
+}
+
+impl Display for SessionEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEvent::Input(value) => write!(f, "in {}", value),
+            SessionEvent::Output(value) => write!(f, "out {}", value)
+        }
+    }
+}
+
+/// How a recorded run finished.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SessionEnd {
+    Halted,
+    InputEnded,
+    Fault(String)
+}
+
+impl Display for SessionEnd {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEnd::Halted => write!(f, "end halted"),
+            SessionEnd::InputEnded => write!(f, "end input"),
+            SessionEnd::Fault(message) => write!(f, "end fault {}", message)
+        }
+    }
+}
+
+/// The events of a run. `end` is `None` for sessions saved before endings were recorded, which replay as if the
+/// program halted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+    pub events: Vec<SessionEvent>,
+    pub end: Option<SessionEnd>
+}
+
+impl Session {
+    pub fn parse(text: &str) -> Result<Session, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+
+        match lines.next() {
+            Some((_, line)) if line.trim() == HEADER => (),
+            Some((_, line)) => return Err(format!("unsupported session header: {}", line.trim())),
+            None => return Err("empty session".to_string())
+        }
+
+        let mut session = Session::default();
+
+        for (idx, line) in lines {
+            let error = || format!("line {}: invalid session event: {}", idx + 1, line.trim());
+
+            if session.end.is_some() {
+                return Err(format!("line {}: event after end of session: {}", idx + 1, line.trim()));
+            }
+
+            let (kind, value) = line.trim().split_once(' ').ok_or_else(error)?;
+
+            match (kind, value.trim()) {
+                ("end", "halted") => session.end = Some(SessionEnd::Halted),
+                ("end", "input") => session.end = Some(SessionEnd::InputEnded),
+                ("end", value) => match value.strip_prefix("fault ") {
+                    Some(message) => session.end = Some(SessionEnd::Fault(message.trim().to_string())),
+                    None => return Err(error())
+                },
+                ("in", value) => session.events.push(SessionEvent::Input(value.parse().map_err(|_| error())?)),
+                ("out", value) => session.events.push(SessionEvent::Output(value.parse().map_err(|_| error())?)),
+                _ => return Err(error())
+            }
+        }
+
+        Ok(session)
+    }
+
+    pub fn load(path: &str) -> Result<Session, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+
+        Session::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|err| format!("{}: {}", path, err))
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+
+        if let Some(end) = &self.end {
+            writeln!(f, "{}", end)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Captures the events passing through the inputs and outputs it wraps. Clones share the same session.
+#[derive(Clone, Default)]
+pub struct Recorder {
+    session: Rc<RefCell<Session>>,
+    input_ended: Rc<RefCell<bool>>
+}
+
+impl Recorder {
+    pub fn new() -> Recorder {
+        Recorder::default()
+    }
+
+    pub fn input<I: Input>(&self, inner: I) -> RecordingInput<I> {
+        RecordingInput { inner, recorder: self.clone() }
+    }
+
+    pub fn output<O: Output>(&self, inner: O) -> RecordingOutput<O> {
+        RecordingOutput { inner, recorder: self.clone() }
+    }
+
+    pub fn session(&self) -> Session {
+        self.session.borrow().clone()
+    }
+
+    /// Records how the run finished. A fault raised after the wrapped input ran out or failed is recorded as
+    /// [`SessionEnd::InputEnded`], since a replay reaches that point as a request for input it has no value for.
+    pub fn finish(&self, result: &Result<(), IntcodeError>) {
+        let end = match result {
+            Ok(()) => SessionEnd::Halted,
+            Err(_) if *self.input_ended.borrow() => SessionEnd::InputEnded,
+            Err(IntcodeError::InputUnavailable { .. }) => SessionEnd::InputEnded,
+            Err(err) => SessionEnd::Fault(err.to_string())
+        };
+
+        self.session.borrow_mut().end = Some(end);
+    }
+
+    fn record(&self, event: SessionEvent) {
+        self.session.borrow_mut().events.push(event);
+    }
+}
+
+pub struct RecordingInput<I: Input> {
+    inner: I,
+    recorder: Recorder
+}
+
+impl<I: Input> Input for RecordingInput<I> {
+    fn read(&mut self) -> Result<Option<i64>, String> {
+        match self.inner.read() {
+            Ok(Some(value)) => {
+                self.recorder.record(SessionEvent::Input(value));
+
+                Ok(Some(value))
+            },
+            result => {
+                *self.recorder.input_ended.borrow_mut() = true;
+
+                result
+            }
+        }
+    }
+}
+
+pub struct RecordingOutput<O: Output> {
+    inner: O,
+    recorder: Recorder
+}
+
+impl<O: Output> Output for RecordingOutput<O> {
+    fn write(&mut self, value: i64) -> Result<(), String> {
+        self.inner.write(value)?;
+        self.recorder.record(SessionEvent::Output(value));
+
+        Ok(())
+    }
+}
+
+/// What the program did at the point a replay diverged from its session.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Observed {
+    InputRequested,
+    Output(i64),
+    Halted
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayError {
+    /// The program's behaviour differed from the session at the event with the given index. `expected` is `None` if
+    /// the session had already ended.
+    Divergence { event_idx: usize, expected: Option<SessionEvent>, observed: Observed },
+    Fault(IntcodeError)
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::Divergence { event_idx, expected, observed } => {
+                let expected = expected.map_or_else(|| "end of session".to_string(), |event| event.to_string());
+
+                write!(f, "replay diverged at event {}: expected {}, observed {:?}", event_idx, expected, observed)
+            },
+            ReplayError::Fault(err) => write!(f, "replay faulted: {}", err)
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Runs a program against a recorded session, feeding it the recorded inputs and checking that it consumes and
+/// produces values in exactly the recorded order, then finishes the way the recorded run did.
+pub fn replay(program: &[i64], session: &Session) -> Result<(), ReplayError> {
+    let mut machine = Machine::new(program);
+    let mut events = session.events.iter().copied().enumerate().peekable();
+
+    loop {
+        let observed = match machine.run_until_event() {
+            Ok(RunStatus::NeedsInput) => Observed::InputRequested,
+            Ok(RunStatus::Output(value)) => Observed::Output(value),
+            Ok(RunStatus::Halted) => Observed::Halted,
+            Err(err) => return match (events.peek(), &session.end) {
+                (None, Some(SessionEnd::Fault(message))) if *message == err.to_string() => Ok(()),
+                _ => Err(ReplayError::Fault(err))
+            }
+        };
+
+        match (events.next(), observed) {
+            (Some((_, SessionEvent::Input(value))), Observed::InputRequested) => machine.push_input(value),
+            (Some((_, SessionEvent::Output(expected))), Observed::Output(value)) if expected == value => (),
+            (None, Observed::Halted) if matches!(session.end, None | Some(SessionEnd::Halted)) => return Ok(()),
+            (None, Observed::InputRequested) if session.end == Some(SessionEnd::InputEnded) => return Ok(()),
+            (next, observed) => return Err(ReplayError::Divergence {
+                event_idx: next.map_or(session.events.len(), |(idx, _)| idx),
+                expected: next.map(|(_, event)| event),
+                observed
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::io::IterInput;
+    use crate::machine::Machine;
+    use crate::session::{replay, Observed, Recorder, ReplayError, Session, SessionEnd, SessionEvent};
+
+    // Outputs 999, 1000 or 1001 as its input is below, equal to or above 8.
+    const COMPARE_TO_8: [i64; 47] = [
+        3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,
+        1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99
+    ];
+
+    fn record(program: &[i64], inputs: Vec<i64>) -> Session {
+        let recorder = Recorder::new();
+
+        let result = Machine::with_io(program, recorder.input(IterInput::new(inputs)), recorder.output(Vec::new())).run();
+
+        recorder.finish(&result);
+        recorder.session()
+    }
+
+    #[test]
+    fn records_inputs_and_outputs_in_order() {
+        assert_eq!(record(&[3, 0, 4, 0, 3, 0, 4, 0, 99], vec![4, -2]).events, vec![
+            SessionEvent::Input(4), SessionEvent::Output(4), SessionEvent::Input(-2), SessionEvent::Output(-2)
+        ]);
+    }
+
+    #[test]
+    fn session_text_round_trip() {
+        let session = record(&COMPARE_TO_8, vec![9]);
+
+        assert_eq!(session.to_string(), "intcode-session 1\nin 9\nout 1001\nend halted\n");
+        assert_eq!(Session::parse(&session.to_string()), Ok(session));
+    }
+
+    #[test]
+    fn invalid_session_text_is_error() {
+        assert!(Session::parse("intcode-session 2\nin 1").is_err());
+        assert!(Session::parse("intcode-session 1\nin x").is_err());
+        assert!(Session::parse("intcode-session 1\nread 1").is_err());
+        assert!(Session::parse("intcode-session 1\nend stopped").is_err());
+        assert!(Session::parse("intcode-session 1\nend halted\nout 1").is_err());
+    }
+
+    #[test]
+    fn replay_matches_recording() {
+        assert_eq!(replay(&COMPARE_TO_8, &record(&COMPARE_TO_8, vec![8])), Ok(()));
+    }
+
+    #[test]
+    fn replay_flags_changed_output() {
+        let mut program = COMPARE_TO_8;
+        program[24] = 250;
+
+        assert_eq!(replay(&program, &record(&COMPARE_TO_8, vec![8])), Err(ReplayError::Divergence {
+            event_idx: 1, expected: Some(SessionEvent::Output(1000)), observed: Observed::Output(2000)
+        }));
+    }
+
+    #[test]
+    fn replay_flags_unexpected_input_request() {
+        let session = record(&[3, 0, 4, 0, 99], vec![1]);
+
+        assert_eq!(replay(&[3, 0, 3, 0, 4, 0, 99], &session), Err(ReplayError::Divergence {
+            event_idx: 1, expected: Some(SessionEvent::Output(1)), observed: Observed::InputRequested
+        }));
+    }
+
+    #[test]
+    fn replay_flags_early_halt() {
+        let session = record(&[104, 1, 104, 2, 99], vec![]);
+
+        assert_eq!(replay(&[104, 1, 99], &session), Err(ReplayError::Divergence {
+            event_idx: 1, expected: Some(SessionEvent::Output(2)), observed: Observed::Halted
+        }));
+    }
+
+    #[test]
+    fn replay_flags_extra_output() {
+        assert_eq!(replay(&[104, 1, 99], &Session::default()), Err(ReplayError::Divergence {
+            event_idx: 0, expected: None, observed: Observed::Output(1)
+        }));
+    }
+
+    #[test]
+    fn records_how_the_run_ended() {
+        assert_eq!(record(&[3, 0, 3, 0, 99], vec![5]).end, Some(SessionEnd::InputEnded));
+        assert_eq!(record(&[104, 1, 99], vec![]).end, Some(SessionEnd::Halted));
+        assert_eq!(record(&[104, 1, 42], vec![]).end, Some(SessionEnd::Fault(
+            "invalid op code at instruction pointer=2: 42: invalid op code identifier: 42".to_string()
+        )));
+    }
+
+    #[test]
+    fn replay_matches_recorded_input_end() {
+        let session = record(&[3, 0, 4, 0, 3, 0, 99], vec![7]);
+
+        assert_eq!(Session::parse(&session.to_string()), Ok(session.clone()));
+        assert_eq!(replay(&[3, 0, 4, 0, 3, 0, 99], &session), Ok(()));
+        assert_eq!(replay(&[3, 0, 4, 0, 99], &session), Err(ReplayError::Divergence {
+            event_idx: 2, expected: None, observed: Observed::Halted
+        }));
+    }
+
+    #[test]
+    fn replay_matches_recorded_fault() {
+        let session = record(&[104, 1, 42], vec![]);
+
+        assert_eq!(Session::parse(&session.to_string()), Ok(session.clone()));
+        assert_eq!(replay(&[104, 1, 42], &session), Ok(()));
+        assert!(matches!(replay(&[104, 1, 43], &session), Err(ReplayError::Fault(_))));
+    }
+}
@@ -0,0 +1,194 @@
+//! Saved machine state. Snapshots are stored as text: a version header followed by one `key value` line per field,
+//! with memory and pending input as comma-separated lists:
+//!
+//! ```text
+//! intcode-snapshot 1
+//! ip 2
+//! rb 0
+//! halted false
+//! steps 1
+//! input 5,6
+//! memory 3,0,4,0,99
+//! ```
+
+use crate::input::parse_words;
+use crate::word::Word;
+use std::fmt::{Display, Formatter};
+use std::fs;
+
+const HEADER: &str = "intcode-snapshot 1";
+
+/// Everything needed to resume a machine whose input is an in-memory queue. `pending_input` holds the queued values
+/// not yet consumed, in the order they will be consumed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot<W = i64> {
+    pub memory: Vec<W>,
+    pub instruction_ptr: usize,
+    pub relative_base: i64,
+    pub halted: bool,
+    pub steps: u64,
+    pub pending_input: Vec<W>
+}
+
+fn join<W: Display>(values: &[W]) -> String {
+    values.iter().map(ToString::to_string).collect::<Vec<String>>().join(",")
+}
+
+fn parse_list<W: Word>(value: &str) -> Result<Vec<W>, String> {
+    if value.is_empty() {
+        Ok(Vec::new())
+    } else {
+        parse_words(value)
+    }
+}
+
+impl<W: Word> Snapshot<W> {
+    /// Parses a snapshot of a machine with words of any size.
+    pub fn parse_words(text: &str) -> Result<Snapshot<W>, String> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+
+        match lines.next() {
+            Some(HEADER) => (),
+            Some(line) => return Err(format!("unsupported snapshot header: {}", line)),
+            None => return Err("empty snapshot".to_string())
+        }
+
+        let mut fields = [None, None, None, None, None, None];
+        const KEYS: [&str; 6] = ["ip", "rb", "halted", "steps", "input", "memory"];
+
+        for line in lines {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let idx = KEYS.iter().position(|known| *known == key).ok_or_else(|| format!("unknown snapshot field: {}", key))?;
+
+            if fields[idx].replace(value.trim()).is_some() {
+                return Err(format!("duplicate snapshot field: {}", key));
+            }
+        }
+
+        let field = |idx: usize| fields[idx].ok_or_else(|| format!("missing snapshot field: {}", KEYS[idx]));
+        let invalid = |idx: usize| format!("invalid snapshot field: {}", KEYS[idx]);
+
+        Ok(Snapshot {
+            instruction_ptr: field(0)?.parse().map_err(|_| invalid(0))?,
+            relative_base: field(1)?.parse().map_err(|_| invalid(1))?,
+            halted: field(2)?.parse().map_err(|_| invalid(2))?,
+            steps: field(3)?.parse().map_err(|_| invalid(3))?,
+            pending_input: parse_list(field(4)?).map_err(|_| invalid(4))?,
+            memory: parse_list(field(5)?).map_err(|_| invalid(5))?
+        })
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        fs::write(path, self.to_string()).map_err(|err| format!("{}: {}", path, err))
+    }
+}
+
+impl Snapshot {
+    pub fn parse(text: &str) -> Result<Snapshot, String> {
+        Snapshot::parse_words(text)
+    }
+
+    pub fn load(path: &str) -> Result<Snapshot, String> {
+        let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path, err))?;
+
+        Snapshot::parse(&text).map_err(|err| format!("{}: {}", path, err))
+    }
+}
+
+impl<W: Display> Display for Snapshot<W> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", HEADER)?;
+        writeln!(f, "ip {}", self.instruction_ptr)?;
+        writeln!(f, "rb {}", self.relative_base)?;
+        writeln!(f, "halted {}", self.halted)?;
+        writeln!(f, "steps {}", self.steps)?;
+        writeln!(f, "input {}", join(&self.pending_input))?;
+        writeln!(f, "memory {}", join(&self.memory))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine::{Machine, RunStatus};
+    use crate::snapshot::Snapshot;
+
+    const ADD_INPUTS: [i64; 14] = [3, 11, 3, 12, 1, 11, 12, 13, 4, 13, 99, 0, 0, 0];
+
+    #[test]
+    fn text_round_trip() {
+        let mut machine = Machine::new(&[3, 0, 4, 0, 99]);
+
+        machine.push_input(5);
+        machine.push_input(6);
+        machine.step().unwrap();
+
+        let snapshot = machine.snapshot();
+
+        assert_eq!(snapshot.to_string(), "intcode-snapshot 1\nip 2\nrb 0\nhalted false\nsteps 1\ninput 6\nmemory 5,0,4,0,99\n");
+        assert_eq!(Snapshot::parse(&snapshot.to_string()), Ok(snapshot));
+    }
+
+    #[test]
+    fn empty_lists_round_trip() {
+        let snapshot = Machine::new(&[]).snapshot();
+
+        assert_eq!(Snapshot::parse(&snapshot.to_string()), Ok(snapshot));
+    }
+
+    #[test]
+    fn invalid_snapshot_text_is_error() {
+        assert!(Snapshot::parse("intcode-snapshot 2\n").is_err());
+        assert!(Snapshot::parse("intcode-snapshot 1\nip 0\nrb 0\nhalted false\nsteps 0\ninput\n").is_err());
+        assert!(Snapshot::parse("intcode-snapshot 1\nip -1\nrb 0\nhalted false\nsteps 0\ninput\nmemory 99\n").is_err());
+        assert!(Snapshot::parse("intcode-snapshot 1\nip 0\nip 0\n").is_err());
+    }
+
+    #[test]
+    fn restored_machine_resumes() {
+        let mut machine = Machine::new(&ADD_INPUTS);
+
+        machine.push_input(40);
+        assert_eq!(machine.run_until_event(), Ok(RunStatus::NeedsInput));
+
+        let mut restored = Machine::from_snapshot(&Snapshot::parse(&machine.snapshot().to_string()).unwrap());
+
+        restored.push_input(2);
+
+        assert_eq!(restored.run_until_event(), Ok(RunStatus::Output(42)));
+        assert_eq!(restored.steps(), 4);
+    }
+
+    #[test]
+    fn restore_rewinds_machine() {
+        let mut machine = Machine::new(&ADD_INPUTS);
+
+        machine.push_input(1);
+        machine.push_input(2);
+        machine.step().unwrap();
+
+        let snapshot = machine.snapshot();
+
+        machine.run().unwrap();
+        machine.restore(&snapshot);
+
+        assert!(!machine.is_halted());
+        assert_eq!(machine.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn clones_run_independently() {
+        let mut machine = Machine::new(&ADD_INPUTS);
+
+        machine.push_input(1);
+        machine.step().unwrap();
+
+        let mut fork = machine.clone();
+
+        machine.push_input(2);
+        fork.push_input(3);
+        machine.run().unwrap();
+        fork.run().unwrap();
+
+        assert_eq!((machine.outputs(), fork.outputs()), (&[3][..], &[4][..]));
+    }
+}
@@ -0,0 +1,662 @@
+//! Symbolic execution. Chosen memory cells, and input values not supplied up front, are variables; arithmetic and
+//! comparison on them builds expressions instead of numbers. A jump whose condition depends on a variable is explored
+//! both ways, each path recording the constraint under which it is taken.
+
+use crate::error::IntcodeError;
+use crate::machine::Machine;
+use crate::opcode::OpCode;
+use crate::optype::{OpType, ParameterMode};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+use std::rc::Rc;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Const(i64),
+    Var(String),
+    Add(Rc<Expr>, Rc<Expr>),
+    Mul(Rc<Expr>, Rc<Expr>),
+    LessThan(Rc<Expr>, Rc<Expr>),
+    Equals(Rc<Expr>, Rc<Expr>),
+    /// The value in memory at an address that depends on variables.
+    Read(Rc<Expr>)
+}
+
+impl Expr {
+    pub fn var(name: &str) -> Expr {
+        Expr::Var(name.to_string())
+    }
+
+    pub fn as_const(&self) -> Option<i64> {
+        match self {
+            Expr::Const(value) => Some(*value),
+            _ => None
+        }
+    }
+
+    /// Sum of two expressions, or `None` if both are constants whose sum overflows.
+    pub fn sum(left: Expr, right: Expr) -> Option<Expr> {
+        Some(match (left.as_const(), right.as_const()) {
+            (Some(left), Some(right)) => Expr::Const(left.checked_add(right)?),
+            (Some(0), _) => right,
+            (_, Some(0)) => left,
+            _ => Expr::Add(Rc::new(left), Rc::new(right))
+        })
+    }
+
+    /// Product of two expressions, or `None` if both are constants whose product overflows.
+    pub fn product(left: Expr, right: Expr) -> Option<Expr> {
+        Some(match (left.as_const(), right.as_const()) {
+            (Some(left), Some(right)) => Expr::Const(left.checked_mul(right)?),
+            (Some(0), _) | (_, Some(0)) => Expr::Const(0),
+            (Some(1), _) => right,
+            (_, Some(1)) => left,
+            _ => Expr::Mul(Rc::new(left), Rc::new(right))
+        })
+    }
+
+    pub fn less_than(left: Expr, right: Expr) -> Expr {
+        match (left.as_const(), right.as_const()) {
+            (Some(left), Some(right)) => Expr::Const(if left < right { 1 } else { 0 }),
+            _ => Expr::LessThan(Rc::new(left), Rc::new(right))
+        }
+    }
+
+    pub fn equals(left: Expr, right: Expr) -> Expr {
+        match (left.as_const(), right.as_const()) {
+            (Some(left), Some(right)) => Expr::Const(if left == right { 1 } else { 0 }),
+            // Reads do not record when they happened, so equal reads either side of a write may differ.
+            _ if left == right && !left.contains_read() => Expr::Const(1),
+            _ => Expr::Equals(Rc::new(left), Rc::new(right))
+        }
+    }
+
+    fn contains_read(&self) -> bool {
+        match self {
+            Expr::Const(_) | Expr::Var(_) => false,
+            Expr::Add(left, right) | Expr::Mul(left, right) | Expr::LessThan(left, right) | Expr::Equals(left, right) =>
+                left.contains_read() || right.contains_read(),
+            Expr::Read(_) => true
+        }
+    }
+
+    /// Evaluates the expression under an assignment of values to variables, or returns `None` if it involves an
+    /// unassigned variable or a read through a symbolic address, or if its arithmetic overflows, as the machine would
+    /// fault.
+    pub fn eval(&self, assignment: &HashMap<String, i64>) -> Option<i64> {
+        Some(match self {
+            Expr::Const(value) => *value,
+            Expr::Var(name) => *assignment.get(name)?,
+            Expr::Add(left, right) => left.eval(assignment)?.checked_add(right.eval(assignment)?)?,
+            Expr::Mul(left, right) => left.eval(assignment)?.checked_mul(right.eval(assignment)?)?,
+            Expr::LessThan(left, right) => if left.eval(assignment)? < right.eval(assignment)? { 1 } else { 0 },
+            Expr::Equals(left, right) => if left.eval(assignment)? == right.eval(assignment)? { 1 } else { 0 },
+            Expr::Read(_) => return None
+        })
+    }
+
+    /// Names of the variables in the expression, in order of first appearance.
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables = Vec::new();
+
+        self.collect_variables(&mut variables);
+
+        variables
+    }
+
+    fn collect_variables(&self, variables: &mut Vec<String>) {
+        match self {
+            Expr::Const(_) => (),
+            Expr::Var(name) => if !variables.contains(name) {
+                variables.push(name.clone());
+            },
+            Expr::Add(left, right) | Expr::Mul(left, right) | Expr::LessThan(left, right) | Expr::Equals(left, right) => {
+                left.collect_variables(variables);
+                right.collect_variables(variables);
+            },
+            Expr::Read(address) => address.collect_variables(variables)
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Const(value) => write!(f, "{}", value),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Add(left, right) => write!(f, "({} + {})", left, right),
+            Expr::Mul(left, right) => write!(f, "({} * {})", left, right),
+            Expr::LessThan(left, right) => write!(f, "({} < {})", left, right),
+            Expr::Equals(left, right) => write!(f, "({} == {})", left, right),
+            Expr::Read(address) => write!(f, "mem[{}]", address)
+        }
+    }
+}
+
+/// Condition that an expression is non-zero, or zero if `nonzero` is false.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Constraint {
+    pub expr: Expr,
+    pub nonzero: bool
+}
+
+impl Constraint {
+    pub fn is_satisfied_by(&self, assignment: &HashMap<String, i64>) -> Option<bool> {
+        self.expr.eval(assignment).map(|value| (value != 0) == self.nonzero)
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} 0", self.expr, if self.nonzero { "!=" } else { "==" })
+    }
+}
+
+/// Why exploration of a path stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathEnd {
+    Halted,
+    StepLimit,
+    PathLimit,
+    /// The machine would fault, as on arithmetic overflow.
+    Fault(IntcodeError),
+    Unsupported(String)
+}
+
+/// One explored path through the program. `inputs` names the variables standing for values read by INPUT
+/// instructions beyond those supplied up front.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    pub end: PathEnd,
+    pub constraints: Vec<Constraint>,
+    pub inputs: Vec<String>,
+    pub outputs: Vec<Expr>,
+    pub memory: Vec<Expr>,
+    pub steps: usize
+}
+
+#[derive(Clone)]
+struct State {
+    memory: Vec<Expr>,
+    instruction_ptr: usize,
+    relative_base: i64,
+    constraints: Vec<Constraint>,
+    inputs: Vec<String>,
+    num_inputs_read: usize,
+    outputs: Vec<Expr>,
+    steps: usize
+}
+
+enum Address {
+    Concrete(usize),
+    Symbolic(Expr)
+}
+
+enum Step {
+    Continue,
+    Fork { condition: Expr, target: usize, next: usize, jump_if_nonzero: bool },
+    End(PathEnd)
+}
+
+impl State {
+    fn word(&self, address: usize) -> Expr {
+        self.memory.get(address).cloned().unwrap_or(Expr::Const(0))
+    }
+
+    fn address(&self, mode: ParameterMode, param_idx: usize) -> Result<Address, PathEnd> {
+        let value = self.word(self.instruction_ptr + 1 + param_idx);
+        let value = match mode {
+            ParameterMode::IMMEDIATE => return Ok(Address::Concrete(self.instruction_ptr + 1 + param_idx)),
+            ParameterMode::POSITION => value,
+            ParameterMode::RELATIVE => match value.as_const() {
+                Some(offset) => Expr::Const(self.relative_base.saturating_add(offset)),
+                None => Expr::Add(Rc::new(Expr::Const(self.relative_base)), Rc::new(value))
+            }
+        };
+
+        match value.as_const() {
+            Some(address) if address < 0 =>
+                Err(PathEnd::Unsupported(format!("negative address {} at instruction pointer={}", address, self.instruction_ptr))),
+            Some(address) => Ok(Address::Concrete(address as usize)),
+            None => Ok(Address::Symbolic(value))
+        }
+    }
+
+    fn read(&self, mode: ParameterMode, param_idx: usize) -> Result<Expr, PathEnd> {
+        Ok(match self.address(mode, param_idx)? {
+            Address::Concrete(address) => self.word(address),
+            Address::Symbolic(address) => Expr::Read(Rc::new(address))
+        })
+    }
+
+    fn write(&mut self, mode: ParameterMode, param_idx: usize, value: Expr) -> Result<(), PathEnd> {
+        match self.address(mode, param_idx)? {
+            Address::Concrete(address) => {
+                if address >= self.memory.len() {
+                    if address >= Machine::DEFAULT_MEMORY_LIMIT {
+                        return Err(PathEnd::Unsupported(format!(
+                            "write to address {} beyond the memory limit of {} words at instruction pointer={}",
+                            address, Machine::DEFAULT_MEMORY_LIMIT, self.instruction_ptr
+                        )));
+                    }
+
+                    self.memory.resize(address + 1, Expr::Const(0));
+                }
+
+                self.memory[address] = value;
+
+                Ok(())
+            },
+            Address::Symbolic(address) =>
+                Err(PathEnd::Unsupported(format!("write to symbolic address {} at instruction pointer={}", address, self.instruction_ptr)))
+        }
+    }
+
+    fn concrete(&self, value: Expr, what: &str) -> Result<i64, PathEnd> {
+        value.as_const().ok_or_else(|| {
+            PathEnd::Unsupported(format!("symbolic {} {} at instruction pointer={}", what, value, self.instruction_ptr))
+        })
+    }
+
+    fn overflow(&self, op_code: i64) -> PathEnd {
+        PathEnd::Fault(IntcodeError::Overflow { instruction_ptr: self.instruction_ptr, op_code })
+    }
+
+    fn step(&mut self, concrete_inputs: &[i64]) -> Result<Step, PathEnd> {
+        if self.instruction_ptr >= self.memory.len() {
+            return Ok(Step::End(PathEnd::Halted));
+        }
+
+        let op_code_value = self.concrete(self.word(self.instruction_ptr), "op code")?;
+        let op_code = OpCode::parse(op_code_value).map_err(|err| {
+            PathEnd::Unsupported(format!("invalid op code {} at instruction pointer={}: {}", op_code_value, self.instruction_ptr, err))
+        })?;
+        let modes = op_code.param_modes();
+        let mut next_instruction_ptr = self.instruction_ptr + op_code.instruction_len();
+
+        match op_code.op_type {
+            OpType::ADD => {
+                let sum = Expr::sum(self.read(modes[0], 0)?, self.read(modes[1], 1)?)
+                    .ok_or_else(|| self.overflow(op_code_value))?;
+
+                self.write(modes[2], 2, sum)?
+            },
+            OpType::MULTIPLY => {
+                let product = Expr::product(self.read(modes[0], 0)?, self.read(modes[1], 1)?)
+                    .ok_or_else(|| self.overflow(op_code_value))?;
+
+                self.write(modes[2], 2, product)?
+            },
+            OpType::LESS_THAN => self.write(modes[2], 2, Expr::less_than(self.read(modes[0], 0)?, self.read(modes[1], 1)?))?,
+            OpType::EQUALS => self.write(modes[2], 2, Expr::equals(self.read(modes[0], 0)?, self.read(modes[1], 1)?))?,
+            OpType::INPUT => {
+                let value = match concrete_inputs.get(self.num_inputs_read) {
+                    Some(value) => Expr::Const(*value),
+                    None => {
+                        let name = format!("input{}", self.num_inputs_read);
+
+                        self.inputs.push(name.clone());
+
+                        Expr::Var(name)
+                    }
+                };
+
+                self.num_inputs_read += 1;
+                self.write(modes[0], 0, value)?;
+            },
+            OpType::OUTPUT => {
+                let value = self.read(modes[0], 0)?;
+
+                self.outputs.push(value);
+            },
+            OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE => {
+                let condition = self.read(modes[0], 0)?;
+                let target = self.concrete(self.read(modes[1], 1)?, "jump target")?;
+                let target = usize::try_from(target).map_err(|_| {
+                    PathEnd::Unsupported(format!("negative jump target {} at instruction pointer={}", target, self.instruction_ptr))
+                })?;
+                let jump_if_nonzero = op_code.op_type == OpType::JUMP_IF_TRUE;
+
+                match condition.as_const() {
+                    Some(value) if (value != 0) == jump_if_nonzero => next_instruction_ptr = target,
+                    Some(_) => (),
+                    None => return Ok(Step::Fork { condition, target, next: next_instruction_ptr, jump_if_nonzero })
+                }
+            },
+            OpType::ADJUST_RELATIVE_BASE => {
+                let offset = self.read(modes[0], 0)?;
+
+                self.relative_base = self.relative_base.saturating_add(self.concrete(offset, "relative base offset")?);
+            },
+            OpType::TERMINATE => return Ok(Step::End(PathEnd::Halted))
+        }
+
+        self.instruction_ptr = next_instruction_ptr;
+
+        Ok(Step::Continue)
+    }
+
+    fn into_path(self, end: PathEnd) -> Path {
+        Path {
+            end,
+            constraints: self.constraints,
+            inputs: self.inputs,
+            outputs: self.outputs,
+            memory: self.memory,
+            steps: self.steps
+        }
+    }
+}
+
+/// Explores the paths through a program. Input values pushed before exploring are consumed first; any further input
+/// is a fresh variable named `input<n>`, counting all inputs read from zero.
+pub struct SymbolicExecutor {
+    memory: Vec<Expr>,
+    concrete_inputs: Vec<i64>,
+    max_paths: usize,
+    max_steps: usize
+}
+
+impl SymbolicExecutor {
+    pub const DEFAULT_MAX_PATHS: usize = 256;
+    pub const DEFAULT_MAX_STEPS: usize = 100_000;
+
+    pub fn new(program: &[i64]) -> SymbolicExecutor {
+        SymbolicExecutor {
+            memory: program.iter().map(|value| Expr::Const(*value)).collect(),
+            concrete_inputs: Vec::new(),
+            max_paths: SymbolicExecutor::DEFAULT_MAX_PATHS,
+            max_steps: SymbolicExecutor::DEFAULT_MAX_STEPS
+        }
+    }
+
+    /// Replaces the value at an address with a variable. Panics if the address is beyond the memory limit of a machine.
+    pub fn set_symbolic(&mut self, address: usize, name: &str) {
+        if address >= self.memory.len() {
+            assert!(address < Machine::DEFAULT_MEMORY_LIMIT, "address {} is beyond the memory limit of {} words",
+                    address, Machine::DEFAULT_MEMORY_LIMIT);
+
+            self.memory.resize(address + 1, Expr::Const(0));
+        }
+
+        self.memory[address] = Expr::var(name);
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.concrete_inputs.push(value);
+    }
+
+    /// Limits the number of paths explored. A path that would fork beyond the limit ends instead.
+    pub fn set_max_paths(&mut self, max_paths: usize) {
+        self.max_paths = max_paths;
+    }
+
+    /// Limits the number of instructions executed along each path.
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = max_steps;
+    }
+
+    pub fn explore(&self) -> Vec<Path> {
+        let mut pending = vec![State {
+            memory: self.memory.clone(),
+            instruction_ptr: 0,
+            relative_base: 0,
+            constraints: Vec::new(),
+            inputs: Vec::new(),
+            num_inputs_read: 0,
+            outputs: Vec::new(),
+            steps: 0
+        }];
+        let mut paths = Vec::new();
+
+        while let Some(mut state) = pending.pop() {
+            let end = loop {
+                if state.steps >= self.max_steps {
+                    break PathEnd::StepLimit;
+                }
+
+                match state.step(&self.concrete_inputs) {
+                    Ok(Step::Continue) => state.steps += 1,
+                    Ok(Step::Fork { condition, target, next, jump_if_nonzero }) => {
+                        if paths.len() + pending.len() + 2 > self.max_paths {
+                            break PathEnd::PathLimit;
+                        }
+
+                        state.steps += 1;
+
+                        let mut jumped = state.clone();
+                        jumped.constraints.push(Constraint { expr: condition.clone(), nonzero: jump_if_nonzero });
+                        jumped.instruction_ptr = target;
+
+                        state.constraints.push(Constraint { expr: condition, nonzero: !jump_if_nonzero });
+                        state.instruction_ptr = next;
+
+                        pending.push(jumped);
+                    },
+                    Ok(Step::End(end)) | Err(end) => break end
+                }
+            };
+
+            paths.push(state.into_path(end));
+        }
+
+        paths
+    }
+}
+
+/// Searches for an assignment of values from `domain` to the given variables satisfying every constraint, trying
+/// every combination in turn.
+pub fn find_assignment(constraints: &[Constraint], variables: &[String], domain: RangeInclusive<i64>) -> Option<HashMap<String, i64>> {
+    let mut assignment = variables.iter().map(|name| (name.clone(), *domain.start())).collect::<HashMap<String, i64>>();
+
+    if domain.is_empty() {
+        return None;
+    }
+
+    loop {
+        if constraints.iter().all(|constraint| constraint.is_satisfied_by(&assignment) == Some(true)) {
+            return Some(assignment);
+        }
+
+        // Advance to the next combination, odometer style.
+        let mut idx = 0;
+        loop {
+            let name = variables.get(idx)?;
+            let value = assignment.get_mut(name).unwrap();
+
+            if *value < *domain.end() {
+                *value += 1;
+
+                break;
+            }
+
+            *value = *domain.start();
+            idx += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::IntcodeError;
+    use crate::machine::Machine;
+    use crate::symbolic::{find_assignment, Constraint, Expr, PathEnd, SymbolicExecutor};
+
+    #[test]
+    fn arithmetic_builds_expressions() {
+        let mut executor = SymbolicExecutor::new(&[1, 1, 2, 0, 1002, 0, 3, 0, 1001, 0, 4, 0, 99]);
+
+        executor.set_symbolic(1, "noun");
+        executor.set_symbolic(2, "verb");
+
+        let paths = executor.explore();
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].end, PathEnd::Halted);
+        assert_eq!(paths[0].memory[0].to_string(), "(((mem[noun] + mem[verb]) * 3) + 4)");
+    }
+
+    #[test]
+    fn constant_folding() {
+        assert_eq!(Expr::sum(Expr::Const(2), Expr::Const(3)), Some(Expr::Const(5)));
+        assert_eq!(Expr::product(Expr::var("x"), Expr::Const(1)), Some(Expr::var("x")));
+        assert_eq!(Expr::product(Expr::Const(0), Expr::var("x")), Some(Expr::Const(0)));
+        assert_eq!(Expr::sum(Expr::Const(i64::MAX), Expr::Const(1)), None);
+        assert_eq!(Expr::equals(Expr::var("x"), Expr::var("x")), Expr::Const(1));
+    }
+
+    #[test]
+    fn reads_either_side_of_write_not_folded() {
+        // Reads through the symbolic address x before and after writing 7 to address 50, and compares the two.
+        let program = [1001, 30, 0, 9, 1001, 30, 0, 17, 1001, 0, 0, 40, 1101, 7, 0, 50, 1001, 0, 0, 41, 8, 40, 41, 42, 4, 42, 99];
+        let mut executor = SymbolicExecutor::new(&program);
+
+        executor.set_symbolic(30, "x");
+
+        let paths = executor.explore();
+
+        assert_eq!(paths[0].outputs[0].to_string(), "(mem[x] == mem[x])");
+
+        let mut machine = Machine::new(&program);
+
+        machine.write(30, 50);
+        machine.run().unwrap();
+
+        assert_eq!(machine.outputs(), &[0]);
+    }
+
+    #[test]
+    fn copies_of_variable_compare_equal() {
+        let program = [1, 30, 31, 40, 1101, 7, 0, 20, 1, 30, 31, 41, 8, 40, 41, 42, 4, 42, 99];
+        let mut executor = SymbolicExecutor::new(&program);
+
+        executor.set_symbolic(30, "x");
+
+        assert_eq!(executor.explore()[0].outputs, vec![Expr::Const(1)]);
+
+        for x in &[0, 7, 20, -3] {
+            let mut machine = Machine::new(&program);
+
+            machine.write(30, *x);
+            machine.run().unwrap();
+
+            assert_eq!(machine.outputs(), &[1]);
+        }
+    }
+
+    #[test]
+    fn concrete_program_has_single_path() {
+        let paths = SymbolicExecutor::new(&[109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99])
+            .explore();
+
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].outputs.len(), 16);
+        assert!(paths[0].constraints.is_empty());
+    }
+
+    #[test]
+    fn overflow_ends_path_with_fault() {
+        let paths = SymbolicExecutor::new(&[1102, i64::MAX, 2, 0, 99]).explore();
+
+        assert_eq!(paths[0].end, PathEnd::Fault(IntcodeError::Overflow { instruction_ptr: 0, op_code: 1102 }));
+        assert_eq!(Machine::new(&[1102, i64::MAX, 2, 0, 99]).run(),
+                   Err(IntcodeError::Overflow { instruction_ptr: 0, op_code: 1102 }));
+    }
+
+    #[test]
+    fn overflowing_assignment_not_satisfying() {
+        let sum = Expr::sum(Expr::var("x"), Expr::Const(i64::MAX)).unwrap();
+        let constraint = Constraint { expr: Expr::less_than(sum, Expr::Const(0)), nonzero: true };
+
+        assert_eq!(find_assignment(&[constraint], &["x".to_string()], 0..=5), None);
+    }
+
+    #[test]
+    fn relative_base_saturates() {
+        let program = [109, i64::MAX, 109, 1, 99];
+        let paths = SymbolicExecutor::new(&program).explore();
+        let mut machine = Machine::new(&program);
+
+        machine.run().unwrap();
+
+        assert_eq!(paths[0].end, PathEnd::Halted);
+        assert_eq!(machine.relative_base(), i64::MAX);
+    }
+
+    #[test]
+    fn comparison_output_solved() {
+        // Outputs whether the input equals 8.
+        let paths = SymbolicExecutor::new(&[3, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]).explore();
+        let output = &paths[0].outputs[0];
+
+        assert_eq!(output.to_string(), "(input0 == 8)");
+
+        let wanted = [Constraint { expr: output.clone(), nonzero: true }];
+
+        assert_eq!(find_assignment(&wanted, &paths[0].inputs, 0..=10).unwrap()["input0"], 8);
+    }
+
+    #[test]
+    fn jump_explores_both_branches() {
+        // Outputs 0 if the input was zero and 1 otherwise.
+        let paths = SymbolicExecutor::new(&[3, 3, 1105, -1, 9, 1101, 0, 0, 12, 4, 12, 99, 1]).explore();
+
+        assert_eq!(paths.len(), 2);
+
+        let zero_output = paths.iter().find(|path| path.outputs == vec![Expr::Const(0)]).unwrap();
+
+        assert_eq!(zero_output.constraints[0].to_string(), "input0 == 0");
+        assert_eq!(find_assignment(&zero_output.constraints, &zero_output.inputs, -5..=5).unwrap()["input0"], 0);
+    }
+
+    #[test]
+    fn concrete_inputs_consumed_first() {
+        let mut executor = SymbolicExecutor::new(&[3, 0, 3, 1, 1, 0, 1, 2, 4, 2, 99]);
+
+        executor.push_input(5);
+
+        let paths = executor.explore();
+
+        assert_eq!(paths[0].inputs, vec!["input1".to_string()]);
+        assert_eq!(paths[0].outputs[0].to_string(), "(5 + input1)");
+    }
+
+    #[test]
+    fn symbolic_loop_hits_limits() {
+        // Counts the input down to zero.
+        let mut executor = SymbolicExecutor::new(&[3, 10, 1001, 10, -1, 10, 1005, 10, 2, 99, 0]);
+
+        executor.set_max_paths(5);
+
+        let paths = executor.explore();
+
+        assert_eq!(paths.len(), 5);
+        assert!(paths.iter().any(|path| path.end == PathEnd::PathLimit));
+    }
+
+    #[test]
+    fn symbolic_write_address_unsupported() {
+        let paths = SymbolicExecutor::new(&[3, 3, 3, 0, 99]).explore();
+
+        assert!(matches!(paths[0].end, PathEnd::Unsupported(_)));
+    }
+
+    #[test]
+    fn write_beyond_memory_limit_unsupported() {
+        let paths = SymbolicExecutor::new(&[1101, 1, 1, 1_000_000_000_000_000, 99]).explore();
+
+        assert!(matches!(&paths[0].end, PathEnd::Unsupported(message) if message.contains("memory limit")));
+    }
+
+    #[test]
+    fn unsatisfiable_constraints() {
+        let constraints = [
+            Constraint { expr: Expr::less_than(Expr::var("x"), Expr::Const(0)), nonzero: true },
+            Constraint { expr: Expr::equals(Expr::var("x"), Expr::var("y")), nonzero: true }
+        ];
+
+        assert_eq!(find_assignment(&constraints, &["x".to_string(), "y".to_string()], 0..=3), None);
+    }
+}
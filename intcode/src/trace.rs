@@ -0,0 +1,437 @@
+//! Per-instruction execution traces. A machine with a tracer attached reports every instruction it executes, after
+//! the instruction has taken effect. Traces can be written as JSON Lines, one object per instruction:
+//!
+//! ```text
+//! {"step":0,"ip":0,"op_code":1002,"op":"MUL","operands":[33,3],"rb":0,"write":{"address":4,"old":33,"new":99},"input":null,"output":null,"next_ip":4}
+//! ```
+//!
+//! or as a compact binary log: the magic bytes `ICTR` and a version byte, followed by one record per instruction of
+//! zigzag LEB128 encoded integers. The binary log holds 64-bit values, so tracing a machine with wider words to it
+//! fails at the first value that does not fit in an `i64`.
+
+use crate::opcode::OpCode;
+use crate::optype::OpType;
+use crate::word::Word;
+use std::cell::RefCell;
+use std::io::Write;
+use std::rc::Rc;
+
+const BINARY_MAGIC: &[u8; 4] = b"ICTR";
+const BINARY_VERSION: u8 = 1;
+
+const FLAG_WRITE: u64 = 1;
+const FLAG_INPUT: u64 = 2;
+const FLAG_OUTPUT: u64 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryWrite<W = i64> {
+    pub address: usize,
+    pub old_value: W,
+    pub new_value: W
+}
+
+/// One executed instruction. `operands` holds the resolved values of the read parameters, and `relative_base` the
+/// relative base in effect when the instruction executed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TraceEvent<W = i64> {
+    pub step: u64,
+    pub instruction_ptr: usize,
+    pub op_code: i64,
+    pub op_type: OpType,
+    pub operands: Vec<W>,
+    pub relative_base: i64,
+    pub write: Option<MemoryWrite<W>>,
+    pub input: Option<W>,
+    pub output: Option<W>,
+    pub next_instruction_ptr: usize
+}
+
+/// Receives trace events from a machine. A failure to record is reported by the machine as an I/O fault, after the
+/// traced instruction has taken effect.
+pub trait Tracer<W = i64> {
+    fn record(&mut self, event: &TraceEvent<W>) -> Result<(), String>;
+
+    /// Completes the trace once the machine has stopped, for example by flushing buffered output. The machine does
+    /// not call this itself: take the tracer back with `Machine::take_tracer` and finish it.
+    fn finish(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Collects events in memory. Clones share the same log, so a clone can be handed to a machine and the events read
+/// back through the original.
+#[derive(Clone)]
+pub struct TraceLog<W = i64> {
+    events: Rc<RefCell<Vec<TraceEvent<W>>>>
+}
+
+impl TraceLog {
+    pub fn new() -> TraceLog {
+        TraceLog::default()
+    }
+}
+
+impl<W: Word> TraceLog<W> {
+    pub fn events(&self) -> Vec<TraceEvent<W>> {
+        self.events.borrow().clone()
+    }
+}
+
+impl<W> Default for TraceLog<W> {
+    fn default() -> TraceLog<W> {
+        TraceLog { events: Rc::new(RefCell::new(Vec::new())) }
+    }
+}
+
+impl<W: Word> Tracer<W> for TraceLog<W> {
+    fn record(&mut self, event: &TraceEvent<W>) -> Result<(), String> {
+        self.events.borrow_mut().push(event.clone());
+
+        Ok(())
+    }
+}
+
+/// Writes each event as a line of JSON.
+pub struct JsonLinesTracer<T: Write> {
+    writer: T
+}
+
+impl<T: Write> JsonLinesTracer<T> {
+    pub fn new(writer: T) -> JsonLinesTracer<T> {
+        JsonLinesTracer { writer }
+    }
+}
+
+impl<T: Write, W: Word> Tracer<W> for JsonLinesTracer<T> {
+    fn record(&mut self, event: &TraceEvent<W>) -> Result<(), String> {
+        writeln!(self.writer, "{}", to_json_line(event)).map_err(|err| err.to_string())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|err| err.to_string())
+    }
+}
+
+/// Writes events in the binary log format, starting with its header.
+pub struct BinaryTracer<T: Write> {
+    writer: T
+}
+
+impl<T: Write> BinaryTracer<T> {
+    pub fn new(mut writer: T) -> std::io::Result<BinaryTracer<T>> {
+        writer.write_all(BINARY_MAGIC)?;
+        writer.write_all(&[BINARY_VERSION])?;
+
+        Ok(BinaryTracer { writer })
+    }
+}
+
+impl<T: Write, W: Word> Tracer<W> for BinaryTracer<T> {
+    fn record(&mut self, event: &TraceEvent<W>) -> Result<(), String> {
+        let mut record = Vec::new();
+
+        encode_event(&narrow(event)?, &mut record);
+
+        self.writer.write_all(&record).map_err(|err| err.to_string())
+    }
+
+    fn finish(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|err| err.to_string())
+    }
+}
+
+/// Converts an event to 64-bit words, failing if any value does not fit.
+fn narrow<W: Word>(event: &TraceEvent<W>) -> Result<TraceEvent, String> {
+    let narrow_word = |word: &W| word.to_i64()
+        .ok_or_else(|| format!("step {}: value {} does not fit in the binary trace format", event.step, word));
+    let narrow_option = |word: &Option<W>| word.as_ref().map(narrow_word).transpose();
+
+    Ok(TraceEvent {
+        step: event.step,
+        instruction_ptr: event.instruction_ptr,
+        op_code: event.op_code,
+        op_type: event.op_type,
+        operands: event.operands.iter().map(narrow_word).collect::<Result<Vec<i64>, String>>()?,
+        relative_base: event.relative_base,
+        write: match &event.write {
+            Some(write) => Some(MemoryWrite {
+                address: write.address,
+                old_value: narrow_word(&write.old_value)?,
+                new_value: narrow_word(&write.new_value)?
+            }),
+            None => None
+        },
+        input: narrow_option(&event.input)?,
+        output: narrow_option(&event.output)?,
+        next_instruction_ptr: event.next_instruction_ptr
+    })
+}
+
+fn json_option<W: Word>(value: &Option<W>) -> String {
+    value.as_ref().map(|value| value.to_string()).unwrap_or_else(|| "null".to_string())
+}
+
+/// Formats an event as a single line of JSON, without a trailing newline.
+pub fn to_json_line<W: Word>(event: &TraceEvent<W>) -> String {
+    let operands = event.operands.iter().map(ToString::to_string).collect::<Vec<String>>().join(",");
+    let write = match &event.write {
+        Some(write) => format!("{{\"address\":{},\"old\":{},\"new\":{}}}", write.address, write.old_value, write.new_value),
+        None => "null".to_string()
+    };
+
+    format!("{{\"step\":{},\"ip\":{},\"op_code\":{},\"op\":\"{}\",\"operands\":[{}],\"rb\":{},\"write\":{},\"input\":{},\"output\":{},\"next_ip\":{}}}",
+            event.step, event.instruction_ptr, event.op_code, event.op_type.mnemonic(), operands, event.relative_base,
+            write, json_option(&event.input), json_option(&event.output), event.next_instruction_ptr)
+}
+
+fn encode_unsigned(mut value: u64, bytes: &mut Vec<u8>) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7f) | 0x80);
+
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
+
+fn encode_signed(value: i64, bytes: &mut Vec<u8>) {
+    encode_unsigned(((value << 1) ^ (value >> 63)) as u64, bytes);
+}
+
+fn encode_event(event: &TraceEvent, bytes: &mut Vec<u8>) {
+    let flags = event.write.map_or(0, |_| FLAG_WRITE)
+        | event.input.map_or(0, |_| FLAG_INPUT)
+        | event.output.map_or(0, |_| FLAG_OUTPUT);
+
+    encode_unsigned(event.step, bytes);
+    encode_unsigned(event.instruction_ptr as u64, bytes);
+    encode_signed(event.op_code, bytes);
+    encode_unsigned(event.operands.len() as u64, bytes);
+    for operand in &event.operands {
+        encode_signed(*operand, bytes);
+    }
+    encode_signed(event.relative_base, bytes);
+    encode_unsigned(flags, bytes);
+    if let Some(write) = event.write {
+        encode_unsigned(write.address as u64, bytes);
+        encode_signed(write.old_value, bytes);
+        encode_signed(write.new_value, bytes);
+    }
+    if let Some(input) = event.input {
+        encode_signed(input, bytes);
+    }
+    if let Some(output) = event.output {
+        encode_signed(output, bytes);
+    }
+    encode_unsigned(event.next_instruction_ptr as u64, bytes);
+}
+
+/// Encodes events as a complete binary log, header included.
+pub fn to_binary(events: &[TraceEvent]) -> Vec<u8> {
+    let mut bytes = BINARY_MAGIC.to_vec();
+
+    bytes.push(BINARY_VERSION);
+    for event in events {
+        encode_event(event, &mut bytes);
+    }
+
+    bytes
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize
+}
+
+impl Decoder<'_> {
+    fn unsigned(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        let mut shift = 0;
+
+        loop {
+            let byte = *self.bytes.get(self.pos).ok_or_else(|| format!("truncated record at byte {}", self.pos))?;
+
+            self.pos += 1;
+
+            if shift >= 64 {
+                return Err(format!("integer too long at byte {}", self.pos - 1));
+            }
+
+            value |= u64::from(byte & 0x7f) << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn signed(&mut self) -> Result<i64, String> {
+        let value = self.unsigned()?;
+
+        Ok((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    fn event(&mut self) -> Result<TraceEvent, String> {
+        let step = self.unsigned()?;
+        let instruction_ptr = self.unsigned()? as usize;
+        let op_code = self.signed()?;
+        let op_type = OpCode::parse(op_code).map_err(|err| format!("step {}: {}", step, err))?.op_type;
+        let num_operands = self.unsigned()?;
+        let operands = (0..num_operands).map(|_| self.signed()).collect::<Result<Vec<i64>, String>>()?;
+        let relative_base = self.signed()?;
+        let flags = self.unsigned()?;
+        let write =
+            if flags & FLAG_WRITE != 0 {
+                Some(MemoryWrite { address: self.unsigned()? as usize, old_value: self.signed()?, new_value: self.signed()? })
+            } else {
+                None
+            };
+        let input = if flags & FLAG_INPUT != 0 { Some(self.signed()?) } else { None };
+        let output = if flags & FLAG_OUTPUT != 0 { Some(self.signed()?) } else { None };
+        let next_instruction_ptr = self.unsigned()? as usize;
+
+        Ok(TraceEvent { step, instruction_ptr, op_code, op_type, operands, relative_base, write, input, output, next_instruction_ptr })
+    }
+}
+
+/// Decodes a binary log written by `BinaryTracer` or `to_binary`.
+pub fn from_binary(bytes: &[u8]) -> Result<Vec<TraceEvent>, String> {
+    if bytes.len() < BINARY_MAGIC.len() + 1 || &bytes[..BINARY_MAGIC.len()] != BINARY_MAGIC {
+        return Err("not an intcode trace log".to_string());
+    }
+
+    let version = bytes[BINARY_MAGIC.len()];
+    if version != BINARY_VERSION {
+        return Err(format!("unsupported trace log version: {}", version));
+    }
+
+    let mut decoder = Decoder { bytes, pos: BINARY_MAGIC.len() + 1 };
+    let mut events = Vec::new();
+
+    while decoder.pos < bytes.len() {
+        events.push(decoder.event()?);
+    }
+
+    Ok(events)
+}
+
+/// Returns the index of the first event at which two traces differ, or at which the shorter trace ends.
+pub fn first_divergence(left: &[TraceEvent], right: &[TraceEvent]) -> Option<usize> {
+    left.iter().zip(right).position(|(left, right)| left != right)
+        .or_else(|| if left.len() != right.len() { Some(left.len().min(right.len())) } else { None })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine::Machine;
+    use crate::optype::OpType;
+    use crate::trace::{
+        first_divergence, from_binary, to_binary, to_json_line, BinaryTracer, JsonLinesTracer, MemoryWrite, TraceEvent,
+        TraceLog, Tracer
+    };
+    use std::io::BufWriter;
+
+    fn trace(program: &[i64], inputs: &[i64]) -> Vec<TraceEvent> {
+        let log = TraceLog::new();
+        let mut machine = Machine::new(program);
+
+        for input in inputs {
+            machine.push_input(*input);
+        }
+
+        machine.set_tracer(Box::new(log.clone()));
+        machine.run().unwrap();
+
+        log.events()
+    }
+
+    #[test]
+    fn records_executed_instructions() {
+        assert_eq!(trace(&[1002, 4, 3, 4, 33], &[]), vec![
+            TraceEvent {
+                step: 0, instruction_ptr: 0, op_code: 1002, op_type: OpType::MULTIPLY, operands: vec![33, 3],
+                relative_base: 0, write: Some(MemoryWrite { address: 4, old_value: 33, new_value: 99 }),
+                input: None, output: None, next_instruction_ptr: 4
+            },
+            TraceEvent {
+                step: 1, instruction_ptr: 4, op_code: 99, op_type: OpType::TERMINATE, operands: vec![],
+                relative_base: 0, write: None, input: None, output: None, next_instruction_ptr: 4
+            }
+        ]);
+    }
+
+    #[test]
+    fn records_io_and_relative_base() {
+        let events = trace(&[109, 3, 203, 5, 204, 5, 99, 0, 0], &[-6]);
+
+        assert_eq!(events.iter().map(|event| event.relative_base).collect::<Vec<i64>>(), vec![0, 3, 3, 3]);
+        assert_eq!(events[1].input, Some(-6));
+        assert_eq!(events[1].write, Some(MemoryWrite { address: 8, old_value: 0, new_value: -6 }));
+        assert_eq!(events[2].output, Some(-6));
+    }
+
+    #[test]
+    fn json_line_format() {
+        assert_eq!(to_json_line(&trace(&[1002, 4, 3, 4, 33], &[])[0]), concat!(
+            "{\"step\":0,\"ip\":0,\"op_code\":1002,\"op\":\"MUL\",\"operands\":[33,3],\"rb\":0,",
+            "\"write\":{\"address\":4,\"old\":33,\"new\":99},\"input\":null,\"output\":null,\"next_ip\":4}"
+        ));
+    }
+
+    #[test]
+    fn binary_round_trip() {
+        let events = trace(&[109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99], &[]);
+
+        assert_eq!(from_binary(&to_binary(&events)), Ok(events));
+    }
+
+    #[test]
+    fn finish_flushes_buffered_output() {
+        let mut tracer = JsonLinesTracer::new(BufWriter::new(Vec::new()));
+        let event = trace(&[99], &[]).remove(0);
+
+        tracer.record(&event).unwrap();
+        Tracer::<i64>::finish(&mut tracer).unwrap();
+
+        assert_eq!(tracer.writer.get_ref(), &format!("{}\n", to_json_line(&event)).into_bytes());
+    }
+
+    #[test]
+    fn wide_words_traced() {
+        let log = TraceLog::default();
+        let mut machine = Machine::from_words(&[104i128, i128::from(i64::MAX) + 1, 99]);
+
+        machine.set_tracer(Box::new(log.clone()));
+        machine.run().unwrap();
+
+        assert_eq!(log.events()[0].output, Some(i128::from(i64::MAX) + 1));
+        assert_eq!(to_json_line(&log.events()[0]), concat!(
+            "{\"step\":0,\"ip\":0,\"op_code\":104,\"op\":\"OUT\",\"operands\":[9223372036854775808],\"rb\":0,",
+            "\"write\":null,\"input\":null,\"output\":9223372036854775808,\"next_ip\":2}"
+        ));
+
+        let mut tracer = BinaryTracer::new(Vec::new()).unwrap();
+
+        assert!(tracer.record(&log.events()[0]).is_err());
+        assert_eq!(tracer.record(&log.events()[1]), Ok(()));
+    }
+
+    #[test]
+    fn truncated_binary_is_error() {
+        let bytes = to_binary(&trace(&[3, 0, 4, 0, 99], &[i64::MIN]));
+
+        assert!(from_binary(&bytes[..bytes.len() - 1]).is_err());
+        assert!(from_binary(b"ICTX\x01").is_err());
+    }
+
+    #[test]
+    fn divergence_between_program_versions() {
+        let original = trace(&[1101, 1, 2, 7, 4, 7, 99, 0], &[]);
+        let modified = trace(&[1101, 1, 3, 7, 4, 7, 99, 0], &[]);
+
+        assert_eq!(first_divergence(&original, &original), None);
+        assert_eq!(first_divergence(&original, &modified), Some(0));
+        assert_eq!(first_divergence(&original, &original[..2]), Some(2));
+    }
+}
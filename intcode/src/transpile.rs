@@ -0,0 +1,335 @@
+//! Ahead-of-time translation of Intcode programs into Rust. `transpile` turns a program into a standalone module whose
+//! `run` function executes each statically reachable instruction as straight-line Rust, in one arm of a `match` on the
+//! instruction pointer. Each arm first checks that memory still holds the words it was compiled from. If the program
+//! has since modified them, or control reaches an address that was not compiled, execution falls back to the
+//! interpreter until it returns to unmodified compiled code.
+
+use crate::cfg::ControlFlowGraph;
+use crate::disassembler::{Line, Operand};
+use crate::error::IntcodeError;
+use crate::machine::{Machine, RunStatus};
+use crate::optype::{OpType, ParameterMode};
+use crate::snapshot::Snapshot;
+use std::collections::VecDeque;
+
+/// State of a compiled program: that of a machine whose input and output are in-memory queues.
+#[derive(Clone, Debug, PartialEq)]
+pub struct State {
+    pub memory: Vec<i64>,
+    pub instruction_ptr: usize,
+    pub relative_base: i64,
+    pub halted: bool,
+    pub input: VecDeque<i64>,
+    pub output: Vec<i64>,
+    pub memory_limit: usize
+}
+
+/// Compiled instructions, as their addresses and the words they were compiled from, in address order.
+pub type CompiledCode<'a> = [(usize, &'a [i64])];
+
+impl State {
+    pub fn new(program: &[i64]) -> State {
+        State {
+            memory: program.to_vec(),
+            instruction_ptr: 0,
+            relative_base: 0,
+            halted: false,
+            input: VecDeque::new(),
+            output: Vec::new(),
+            memory_limit: Machine::DEFAULT_MEMORY_LIMIT
+        }
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.input.push_back(value);
+    }
+
+    pub fn read(&self, address: usize) -> i64 {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    /// Writes a value at the given address, zero-filling memory up to it if it lies beyond the end of memory. Panics if
+    /// that would grow memory beyond its limit.
+    pub fn write(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            assert!(address < self.memory_limit, "address {} is beyond the memory limit of {} words", address, self.memory_limit);
+
+            self.memory.resize(address + 1, 0);
+        }
+
+        self.memory[address] = value;
+    }
+
+    /// Stores the result of the instruction at `instruction_ptr`, faulting as the interpreter would if that would grow
+    /// memory beyond its limit.
+    pub fn store(&mut self, instruction_ptr: usize, op_code: i64, param_idx: usize, address: usize, value: i64) -> Result<(), IntcodeError> {
+        if address >= self.memory.len() && address >= self.memory_limit {
+            return Err(IntcodeError::AddressOutOfRange { instruction_ptr, op_code, param_idx, address, limit: self.memory_limit });
+        }
+
+        self.write(address, value);
+
+        Ok(())
+    }
+
+    /// Whether memory at the given address still holds the words of an instruction.
+    pub fn matches(&self, address: usize, words: &[i64]) -> bool {
+        self.memory.get(address..address + words.len()) == Some(words)
+    }
+
+    /// Checks an address computed by the instruction at `instruction_ptr`, failing as the interpreter would.
+    pub fn address(&self, instruction_ptr: usize, op_code: i64, param_idx: usize, address: i64) -> Result<usize, IntcodeError> {
+        if address < 0 {
+            return Err(IntcodeError::NegativeAddress { instruction_ptr, op_code, param_idx, address });
+        }
+
+        Ok(address as usize)
+    }
+
+    pub fn relative(&self, instruction_ptr: usize, op_code: i64, param_idx: usize, offset: i64) -> Result<usize, IntcodeError> {
+        self.address(instruction_ptr, op_code, param_idx, self.relative_base.saturating_add(offset))
+    }
+
+    /// Sum computed by the instruction at `instruction_ptr`, faulting on overflow as the interpreter does by default.
+    pub fn add(&self, instruction_ptr: usize, op_code: i64, left: i64, right: i64) -> Result<i64, IntcodeError> {
+        left.checked_add(right).ok_or(IntcodeError::Overflow { instruction_ptr, op_code })
+    }
+
+    /// Product computed by the instruction at `instruction_ptr`, faulting on overflow as the interpreter does by
+    /// default.
+    pub fn multiply(&self, instruction_ptr: usize, op_code: i64, left: i64, right: i64) -> Result<i64, IntcodeError> {
+        left.checked_mul(right).ok_or(IntcodeError::Overflow { instruction_ptr, op_code })
+    }
+
+    /// Runs compiled code from the current instruction pointer until the program halts or needs input. On a fault the
+    /// instruction pointer is left at the faulting instruction.
+    pub fn run(&mut self, execute: fn(&mut State, usize) -> Result<RunStatus, IntcodeError>) -> Result<RunStatus, IntcodeError> {
+        if self.halted {
+            return Ok(RunStatus::Halted);
+        }
+
+        let result = execute(self, self.instruction_ptr);
+
+        if let Err(error) = &result {
+            self.instruction_ptr = error.instruction_ptr();
+        }
+
+        result
+    }
+
+    /// Interprets instructions from the current instruction pointer until the program halts, needs input, or reaches
+    /// an unmodified compiled instruction. Returns the status the program stopped with, if it stopped.
+    pub fn interpret(&mut self, compiled: &CompiledCode) -> Result<Option<RunStatus>, IntcodeError> {
+        let mut machine = Machine::from_snapshot(&Snapshot {
+            memory: std::mem::take(&mut self.memory),
+            instruction_ptr: self.instruction_ptr,
+            relative_base: self.relative_base,
+            halted: self.halted,
+            steps: 0,
+            pending_input: self.input.drain(..).collect()
+        });
+
+        machine.set_memory_limit(self.memory_limit);
+
+        let result = loop {
+            match machine.step() {
+                Ok(Some(RunStatus::Output(value))) => self.output.push(value),
+                Ok(Some(status)) => break Ok(Some(status)),
+                Ok(None) => (),
+                Err(error) => break Err(error)
+            }
+
+            let instruction_ptr = machine.instruction_ptr();
+            let resumes = compiled.binary_search_by_key(&instruction_ptr, |(address, _)| *address)
+                .is_ok_and(|idx| machine.memory().get(instruction_ptr..instruction_ptr + compiled[idx].1.len()) == Some(compiled[idx].1));
+
+            if resumes {
+                break Ok(None);
+            }
+        };
+
+        let snapshot = machine.snapshot();
+
+        self.memory = snapshot.memory;
+        self.instruction_ptr = snapshot.instruction_ptr;
+        self.relative_base = snapshot.relative_base;
+        self.halted = snapshot.halted;
+        self.input = snapshot.pending_input.into_iter().collect();
+
+        result
+    }
+}
+
+/// Rust expression for the value of a read parameter.
+fn read_operand(address: usize, op_code: i64, param_idx: usize, operand: &Operand) -> String {
+    match operand.mode {
+        ParameterMode::IMMEDIATE => operand.value.to_string(),
+        _ => format!("state.read({})", write_operand(address, op_code, param_idx, operand))
+    }
+}
+
+/// Rust expression for the address referred to by a position or relative mode parameter.
+fn write_operand(address: usize, op_code: i64, param_idx: usize, operand: &Operand) -> String {
+    match operand.mode {
+        ParameterMode::POSITION if operand.value >= 0 => operand.value.to_string(),
+        ParameterMode::RELATIVE => format!("state.relative({}, {}, {}, {})?", address, op_code, param_idx, operand.value),
+        _ => format!("state.address({}, {}, {}, {})?", address, op_code, param_idx, operand.value)
+    }
+}
+
+/// Body of the match arm executing an instruction, evaluating to the address of the next instruction.
+fn instruction_body(address: usize, op_code: i64, op_type: OpType, operands: &[Operand], next: usize) -> Vec<String> {
+    let read = |param_idx: usize| read_operand(address, op_code, param_idx, &operands[param_idx]);
+    let destination = |param_idx: usize| write_operand(address, op_code, param_idx, &operands[param_idx]);
+
+    let store = |value: String| vec![
+        format!("let value = {};", value),
+        format!("state.store({}, {}, 2, {}, value)?;", address, op_code, destination(2)),
+        next.to_string()
+    ];
+
+    match op_type {
+        OpType::ADD => store(format!("state.add({}, {}, {}, {})?", address, op_code, read(0), read(1))),
+        OpType::MULTIPLY => store(format!("state.multiply({}, {}, {}, {})?", address, op_code, read(0), read(1))),
+        OpType::LESS_THAN => store(format!("i64::from({} < {})", read(0), read(1))),
+        OpType::EQUALS => store(format!("i64::from({} == {})", read(0), read(1))),
+        OpType::INPUT => vec![
+            format!("let address = {};", destination(0)),
+            "match state.input.pop_front() {".to_string(),
+            format!("    Some(value) => state.store({}, {}, 0, address, value)?,", address, op_code),
+            "    None => {".to_string(),
+            format!("        state.instruction_ptr = {};", address),
+            "        return Ok(RunStatus::NeedsInput);".to_string(),
+            "    }".to_string(),
+            "}".to_string(),
+            next.to_string()
+        ],
+        OpType::OUTPUT => vec![format!("state.output.push({});", read(0)), next.to_string()],
+        OpType::JUMP_IF_TRUE | OpType::JUMP_IF_FALSE => {
+            let comparison = if op_type == OpType::JUMP_IF_TRUE { "!=" } else { "==" };
+            let target = match operands[1] {
+                Operand { mode: ParameterMode::IMMEDIATE, value } if value >= 0 => value.to_string(),
+                _ => format!("state.address({}, {}, 1, {})?", address, op_code, read(1))
+            };
+
+            match operands[0] {
+                Operand { mode: ParameterMode::IMMEDIATE, value } =>
+                    vec![if (value != 0) == (op_type == OpType::JUMP_IF_TRUE) { target } else { next.to_string() }],
+                _ => vec![
+                    format!("if {} {} 0 {{", read(0), comparison),
+                    format!("    {}", target),
+                    "} else {".to_string(),
+                    format!("    {}", next),
+                    "}".to_string()
+                ]
+            }
+        },
+        OpType::ADJUST_RELATIVE_BASE =>
+            vec![format!("state.relative_base = state.relative_base.saturating_add({});", read(0)), next.to_string()],
+        OpType::TERMINATE => vec![
+            format!("state.instruction_ptr = {};", address),
+            "state.halted = true;".to_string(),
+            "return Ok(RunStatus::Halted)".to_string()
+        ]
+    }
+}
+
+/// Translates a program into the source of a Rust module exposing `run(&mut State) -> Result<RunStatus,
+/// IntcodeError>`, which executes it exactly as a machine with the default word size and overflow policy would. The
+/// module depends only on this crate.
+pub fn transpile(program: &[i64]) -> String {
+    let graph = ControlFlowGraph::build(program);
+    let instructions = graph.blocks.iter()
+        .flat_map(|block| block.lines.iter())
+        .filter_map(|line| match line {
+            Line::Instruction { address, words, op_type, operands } => Some((*address, words, *op_type, operands)),
+            Line::Data { .. } => None
+        })
+        .collect::<Vec<(usize, &Vec<i64>, OpType, &Vec<Operand>)>>();
+
+    let join = |words: &[i64]| words.iter().map(ToString::to_string).collect::<Vec<String>>().join(", ");
+
+    let mut lines = vec![
+        format!("//! Compiled by `intcode::transpile` from a {} word Intcode program.", program.len()),
+        String::new(),
+        "#![allow(clippy::all)]".to_string(),
+        String::new(),
+        "use intcode::transpile::{CompiledCode, State};".to_string(),
+        "use intcode::{IntcodeError, RunStatus};".to_string(),
+        String::new(),
+        "const CODE: &CompiledCode = &[".to_string()
+    ];
+
+    for (address, words, _, _) in &instructions {
+        lines.push(format!("    ({}, &[{}]),", address, join(words)));
+    }
+
+    lines.extend(vec![
+        "];".to_string(),
+        String::new(),
+        "/// Runs the program until it halts or needs input, appending its outputs to `state.output`.".to_string(),
+        "pub fn run(state: &mut State) -> Result<RunStatus, IntcodeError> {".to_string(),
+        "    state.run(execute)".to_string(),
+        "}".to_string(),
+        String::new(),
+        "fn execute(state: &mut State, mut ip: usize) -> Result<RunStatus, IntcodeError> {".to_string(),
+        "    loop {".to_string(),
+        "        ip = match ip {".to_string()
+    ]);
+
+    for (address, words, op_type, operands) in &instructions {
+        let next = address + words.len();
+
+        lines.push(format!("            {} if state.matches({}, &[{}]) => {{", address, address, join(words)));
+        lines.extend(instruction_body(*address, words[0], *op_type, operands, next).into_iter()
+            .map(|line| format!("                {}", line)));
+        lines.push("            },".to_string());
+    }
+
+    lines.extend(vec![
+        "            _ => {".to_string(),
+        "                state.instruction_ptr = ip;".to_string(),
+        String::new(),
+        "                match state.interpret(CODE)? {".to_string(),
+        "                    Some(status) => return Ok(status),".to_string(),
+        "                    None => state.instruction_ptr".to_string(),
+        "                }".to_string(),
+        "            }".to_string(),
+        "        };".to_string(),
+        "    }".to_string(),
+        "}".to_string()
+    ]);
+
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::machine::RunStatus;
+    use crate::transpile::{transpile, State};
+
+    #[test]
+    fn instruction_compiled_with_guard() {
+        let source = transpile(&[1001, 5, -1, 5, 99, 3]);
+
+        assert!(source.contains(concat!(
+            "            0 if state.matches(0, &[1001, 5, -1, 5]) => {\n",
+            "                let value = state.add(0, 1001, state.read(5), -1)?;\n",
+            "                state.store(0, 1001, 2, 5, value)?;\n",
+            "                4\n",
+            "            },\n"
+        )));
+        assert!(source.contains("    (4, &[99]),\n"));
+    }
+
+    #[test]
+    fn interpreter_stops_at_unmodified_compiled_code() {
+        let mut state = State::new(&[104, 7, 104, 8, 99]);
+
+        assert_eq!(state.interpret(&[(2, &[104, 8])]), Ok(None));
+        assert_eq!((state.instruction_ptr, state.output.clone()), (2, vec![7]));
+
+        assert_eq!(state.interpret(&[(2, &[104, 9])]), Ok(Some(RunStatus::Halted)));
+        assert_eq!((state.instruction_ptr, state.output.clone(), state.halted), (4, vec![7, 8], true));
+    }
+}
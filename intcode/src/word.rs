@@ -0,0 +1,131 @@
+//! Machine words. A machine can be built over any `Word` type: the fixed-width `i32`, `i64` and `i128`, or the
+//! arbitrary-precision `BigInt`. Its `OverflowPolicy` decides what ADD and MULTIPLY do with a result that does not fit.
+
+use crate::bigint::BigInt;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
+use std::hash::Hash;
+
+/// What arithmetic does with a result too large or too small for a word.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum OverflowPolicy {
+    /// Wraps around, as two's complement arithmetic does.
+    Wrap,
+    /// Faults with `IntcodeError::Overflow`. This is the default.
+    #[default]
+    Checked,
+    /// Clamps to the largest or smallest representable value.
+    Saturate
+}
+
+pub trait Word: Clone + Debug + Display + Ord + Hash + 'static {
+    fn zero() -> Self;
+    fn from_bool(value: bool) -> Self;
+    fn parse(text: &str) -> Result<Self, String>;
+
+    /// The value as an `i64`, if it fits.
+    fn to_i64(&self) -> Option<i64>;
+
+    /// Sum of two words, or `None` if it overflows under the `Checked` policy.
+    fn add(&self, other: &Self, policy: OverflowPolicy) -> Option<Self>;
+
+    /// Product of two words, or `None` if it overflows under the `Checked` policy.
+    fn multiply(&self, other: &Self, policy: OverflowPolicy) -> Option<Self>;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// The value as an `i64`, clamped to the range of `i64`.
+    fn to_i64_saturating(&self) -> i64 {
+        self.to_i64().unwrap_or(if *self < Self::zero() { i64::MIN } else { i64::MAX })
+    }
+}
+
+macro_rules! fixed_width_word {
+    ($($word:ty),*) => {$(
+        impl Word for $word {
+            fn zero() -> $word {
+                0
+            }
+
+            fn from_bool(value: bool) -> $word {
+                <$word>::from(value)
+            }
+
+            fn parse(text: &str) -> Result<$word, String> {
+                text.parse::<$word>().map_err(|err| err.to_string())
+            }
+
+            fn to_i64(&self) -> Option<i64> {
+                i64::try_from(*self).ok()
+            }
+
+            fn add(&self, other: &$word, policy: OverflowPolicy) -> Option<$word> {
+                match policy {
+                    OverflowPolicy::Wrap => Some(self.wrapping_add(*other)),
+                    OverflowPolicy::Checked => self.checked_add(*other),
+                    OverflowPolicy::Saturate => Some(self.saturating_add(*other))
+                }
+            }
+
+            fn multiply(&self, other: &$word, policy: OverflowPolicy) -> Option<$word> {
+                match policy {
+                    OverflowPolicy::Wrap => Some(self.wrapping_mul(*other)),
+                    OverflowPolicy::Checked => self.checked_mul(*other),
+                    OverflowPolicy::Saturate => Some(self.saturating_mul(*other))
+                }
+            }
+        }
+    )*}
+}
+
+fixed_width_word!(i32, i64, i128);
+
+/// Arbitrary-precision words never overflow, so the policy has no effect.
+impl Word for BigInt {
+    fn zero() -> BigInt {
+        BigInt::default()
+    }
+
+    fn from_bool(value: bool) -> BigInt {
+        BigInt::from(i64::from(value))
+    }
+
+    fn parse(text: &str) -> Result<BigInt, String> {
+        text.parse()
+    }
+
+    fn to_i64(&self) -> Option<i64> {
+        BigInt::to_i64(self)
+    }
+
+    fn add(&self, other: &BigInt, _policy: OverflowPolicy) -> Option<BigInt> {
+        Some(BigInt::add(self, other))
+    }
+
+    fn multiply(&self, other: &BigInt, _policy: OverflowPolicy) -> Option<BigInt> {
+        Some(BigInt::multiply(self, other))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::word::{OverflowPolicy, Word};
+
+    #[test]
+    fn overflow_policies() {
+        assert_eq!(i32::MAX.add(&1, OverflowPolicy::Wrap), Some(i32::MIN));
+        assert_eq!(i32::MAX.add(&1, OverflowPolicy::Checked), None);
+        assert_eq!(i32::MAX.add(&1, OverflowPolicy::Saturate), Some(i32::MAX));
+        assert_eq!(i32::MIN.multiply(&2, OverflowPolicy::Saturate), Some(i32::MIN));
+        assert_eq!(40_000.multiply(&40_000, OverflowPolicy::Checked), Some(1_600_000_000i64));
+    }
+
+    #[test]
+    fn saturating_conversion() {
+        assert_eq!(i128::MAX.to_i64_saturating(), i64::MAX);
+        assert_eq!(i128::MIN.to_i64_saturating(), i64::MIN);
+        assert_eq!((-5i32).to_i64_saturating(), -5);
+    }
+}
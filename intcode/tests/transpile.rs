@@ -0,0 +1,168 @@
+//! Compiles transpiled programs and checks that they behave exactly as the interpreter does. The modules under
+//! `transpiled/` are output of the `transpile` binary, and are checked against `transpile` so that they are
+//! regenerated whenever its output changes.
+
+#[path = "transpiled/comparison.rs"]
+mod comparison;
+#[path = "transpiled/countdown.rs"]
+mod countdown;
+#[path = "transpiled/quine.rs"]
+mod quine;
+#[path = "transpiled/self_modifying.rs"]
+mod self_modifying;
+
+use intcode::snapshot::Snapshot;
+use intcode::transpile::{transpile, State};
+use intcode::{IntcodeError, Machine, RunStatus};
+
+const COMPARISON: [i64; 47] = [
+    3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,
+    1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,
+    999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99
+];
+
+// Decrements the counter at address 8 until it reaches zero.
+const COUNTDOWN: [i64; 9] = [1001, 8, -1, 8, 1005, 8, 0, 99, 1000];
+
+const QUINE: [i64; 16] = [109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99];
+
+// Patches the operand of its OUTPUT instruction before executing it.
+const SELF_MODIFYING: [i64; 7] = [1101, 0, 7, 5, 104, 1, 99];
+
+fn interpret(program: &[i64], inputs: &[i64]) -> (Result<(), IntcodeError>, Vec<i64>, Vec<i64>) {
+    let mut machine = Machine::new(program);
+
+    for input in inputs {
+        machine.push_input(*input);
+    }
+
+    let result = machine.run();
+
+    (result, machine.outputs().to_vec(), machine.memory().to_vec())
+}
+
+fn compiled(run: fn(&mut State) -> Result<RunStatus, IntcodeError>, program: &[i64], inputs: &[i64]) -> (Result<(), IntcodeError>, Vec<i64>, Vec<i64>) {
+    let mut state = State::new(program);
+
+    for input in inputs {
+        state.push_input(*input);
+    }
+
+    let result = match run(&mut state) {
+        Ok(RunStatus::NeedsInput) => Err(IntcodeError::InputUnavailable { instruction_ptr: state.instruction_ptr }),
+        result => result.map(|_| ())
+    };
+
+    (result, state.output, state.memory)
+}
+
+#[test]
+fn transpiled_modules_are_current() {
+    assert_eq!(transpile(&COMPARISON), include_str!("transpiled/comparison.rs"));
+    assert_eq!(transpile(&COUNTDOWN), include_str!("transpiled/countdown.rs"));
+    assert_eq!(transpile(&QUINE), include_str!("transpiled/quine.rs"));
+    assert_eq!(transpile(&SELF_MODIFYING), include_str!("transpiled/self_modifying.rs"));
+}
+
+#[test]
+fn comparison_matches_interpreter() {
+    for input in 6..11 {
+        assert_eq!(compiled(comparison::run, &COMPARISON, &[input]), interpret(&COMPARISON, &[input]));
+    }
+}
+
+#[test]
+fn loop_matches_interpreter() {
+    assert_eq!(compiled(countdown::run, &COUNTDOWN, &[]), interpret(&COUNTDOWN, &[]));
+}
+
+#[test]
+fn relative_mode_matches_interpreter() {
+    assert_eq!(compiled(quine::run, &QUINE, &[]).1, QUINE.to_vec());
+    assert_eq!(compiled(quine::run, &QUINE, &[]), interpret(&QUINE, &[]));
+}
+
+#[test]
+fn modified_code_falls_back_to_interpreter() {
+    assert_eq!(compiled(self_modifying::run, &SELF_MODIFYING, &[]).1, vec![7]);
+    assert_eq!(compiled(self_modifying::run, &SELF_MODIFYING, &[]), interpret(&SELF_MODIFYING, &[]));
+}
+
+#[test]
+fn different_memory_falls_back_to_interpreter() {
+    let mut program = COMPARISON;
+
+    program[32] = 555;
+
+    assert_eq!(compiled(comparison::run, &program, &[7]), interpret(&program, &[7]));
+}
+
+#[test]
+fn resumes_after_input() {
+    let mut state = State::new(&COMPARISON);
+
+    assert_eq!(comparison::run(&mut state), Ok(RunStatus::NeedsInput));
+    assert_eq!(state.instruction_ptr, 0);
+
+    state.push_input(8);
+
+    assert_eq!(comparison::run(&mut state), Ok(RunStatus::Halted));
+    assert_eq!(comparison::run(&mut state), Ok(RunStatus::Halted));
+    assert_eq!(state.output, vec![1000]);
+}
+
+#[test]
+fn fault_reported_at_instruction() {
+    let mut state = State::new(&QUINE);
+
+    state.relative_base = -10;
+
+    assert_eq!(quine::run(&mut state).unwrap_err(), interpret(&[109, -9, 204, -1, 99], &[]).0.unwrap_err());
+    assert_eq!(state.instruction_ptr, 2);
+}
+
+#[test]
+fn overflow_matches_interpreter() {
+    let mut program = QUINE.to_vec();
+
+    program.resize(101, 0);
+    program[100] = i64::MAX;
+
+    assert_eq!(compiled(quine::run, &program, &[]).0, Err(IntcodeError::Overflow { instruction_ptr: 4, op_code: 1001 }));
+    assert_eq!(compiled(quine::run, &program, &[]), interpret(&program, &[]));
+}
+
+#[test]
+fn memory_limit_matches_interpreter() {
+    let mut state = State::new(&QUINE);
+    let mut machine = Machine::new(&QUINE);
+
+    state.memory_limit = 101;
+    machine.set_memory_limit(101);
+
+    let error = IntcodeError::AddressOutOfRange { instruction_ptr: 8, op_code: 1008, param_idx: 2, address: 101, limit: 101 };
+
+    assert_eq!(quine::run(&mut state), Err(error.clone()));
+    assert_eq!(machine.run(), Err(error));
+    assert_eq!(state.memory, machine.memory());
+}
+
+#[test]
+fn relative_base_saturates_as_interpreter() {
+    let mut state = State::new(&QUINE);
+    let mut machine = Machine::from_snapshot(&Snapshot {
+        memory: QUINE.to_vec(),
+        instruction_ptr: 0,
+        relative_base: i64::MAX,
+        halted: false,
+        steps: 0,
+        pending_input: Vec::new()
+    });
+
+    state.relative_base = i64::MAX;
+
+    assert_eq!(quine::run(&mut state), Ok(RunStatus::Halted));
+    assert_eq!(machine.run(), Ok(()));
+    assert_eq!(state.relative_base, i64::MAX);
+    assert_eq!((state.output, state.memory), (machine.outputs().to_vec(), machine.memory().to_vec()));
+}
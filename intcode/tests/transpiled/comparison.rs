@@ -0,0 +1,118 @@
+//! Compiled by `intcode::transpile` from a 47 word Intcode program.
+
+#![allow(clippy::all)]
+
+use intcode::transpile::{CompiledCode, State};
+use intcode::{IntcodeError, RunStatus};
+
+const CODE: &CompiledCode = &[
+    (0, &[3, 21]),
+    (2, &[1008, 21, 8, 20]),
+    (6, &[1005, 20, 22]),
+    (9, &[107, 8, 21, 20]),
+    (13, &[1006, 20, 31]),
+    (16, &[1106, 0, 36]),
+    (22, &[1002, 21, 125, 20]),
+    (26, &[4, 20]),
+    (28, &[1105, 1, 46]),
+    (31, &[104, 999]),
+    (33, &[1105, 1, 46]),
+    (36, &[1101, 1000, 1, 20]),
+    (40, &[4, 20]),
+    (42, &[1105, 1, 46]),
+    (46, &[99]),
+];
+
+/// Runs the program until it halts or needs input, appending its outputs to `state.output`.
+pub fn run(state: &mut State) -> Result<RunStatus, IntcodeError> {
+    state.run(execute)
+}
+
+fn execute(state: &mut State, mut ip: usize) -> Result<RunStatus, IntcodeError> {
+    loop {
+        ip = match ip {
+            0 if state.matches(0, &[3, 21]) => {
+                let address = 21;
+                match state.input.pop_front() {
+                    Some(value) => state.store(0, 3, 0, address, value)?,
+                    None => {
+                        state.instruction_ptr = 0;
+                        return Ok(RunStatus::NeedsInput);
+                    }
+                }
+                2
+            },
+            2 if state.matches(2, &[1008, 21, 8, 20]) => {
+                let value = i64::from(state.read(21) == 8);
+                state.store(2, 1008, 2, 20, value)?;
+                6
+            },
+            6 if state.matches(6, &[1005, 20, 22]) => {
+                if state.read(20) != 0 {
+                    22
+                } else {
+                    9
+                }
+            },
+            9 if state.matches(9, &[107, 8, 21, 20]) => {
+                let value = i64::from(8 < state.read(21));
+                state.store(9, 107, 2, 20, value)?;
+                13
+            },
+            13 if state.matches(13, &[1006, 20, 31]) => {
+                if state.read(20) == 0 {
+                    31
+                } else {
+                    16
+                }
+            },
+            16 if state.matches(16, &[1106, 0, 36]) => {
+                36
+            },
+            22 if state.matches(22, &[1002, 21, 125, 20]) => {
+                let value = state.multiply(22, 1002, state.read(21), 125)?;
+                state.store(22, 1002, 2, 20, value)?;
+                26
+            },
+            26 if state.matches(26, &[4, 20]) => {
+                state.output.push(state.read(20));
+                28
+            },
+            28 if state.matches(28, &[1105, 1, 46]) => {
+                46
+            },
+            31 if state.matches(31, &[104, 999]) => {
+                state.output.push(999);
+                33
+            },
+            33 if state.matches(33, &[1105, 1, 46]) => {
+                46
+            },
+            36 if state.matches(36, &[1101, 1000, 1, 20]) => {
+                let value = state.add(36, 1101, 1000, 1)?;
+                state.store(36, 1101, 2, 20, value)?;
+                40
+            },
+            40 if state.matches(40, &[4, 20]) => {
+                state.output.push(state.read(20));
+                42
+            },
+            42 if state.matches(42, &[1105, 1, 46]) => {
+                46
+            },
+            46 if state.matches(46, &[99]) => {
+                state.instruction_ptr = 46;
+                state.halted = true;
+                return Ok(RunStatus::Halted)
+            },
+            _ => {
+                state.instruction_ptr = ip;
+
+                match state.interpret(CODE)? {
+                    Some(status) => return Ok(status),
+                    None => state.instruction_ptr
+                }
+            }
+        };
+    }
+}
@@ -0,0 +1,49 @@
+//! Compiled by `intcode::transpile` from a 9 word Intcode program.
+
+#![allow(clippy::all)]
+
+use intcode::transpile::{CompiledCode, State};
+use intcode::{IntcodeError, RunStatus};
+
+const CODE: &CompiledCode = &[
+    (0, &[1001, 8, -1, 8]),
+    (4, &[1005, 8, 0]),
+    (7, &[99]),
+];
+
+/// Runs the program until it halts or needs input, appending its outputs to `state.output`.
+pub fn run(state: &mut State) -> Result<RunStatus, IntcodeError> {
+    state.run(execute)
+}
+
+fn execute(state: &mut State, mut ip: usize) -> Result<RunStatus, IntcodeError> {
+    loop {
+        ip = match ip {
+            0 if state.matches(0, &[1001, 8, -1, 8]) => {
+                let value = state.add(0, 1001, state.read(8), -1)?;
+                state.store(0, 1001, 2, 8, value)?;
+                4
+            },
+            4 if state.matches(4, &[1005, 8, 0]) => {
+                if state.read(8) != 0 {
+                    0
+                } else {
+                    7
+                }
+            },
+            7 if state.matches(7, &[99]) => {
+                state.instruction_ptr = 7;
+                state.halted = true;
+                return Ok(RunStatus::Halted)
+            },
+            _ => {
+                state.instruction_ptr = ip;
+
+                match state.interpret(CODE)? {
+                    Some(status) => return Ok(status),
+                    None => state.instruction_ptr
+                }
+            }
+        };
+    }
+}
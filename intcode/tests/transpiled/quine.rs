@@ -0,0 +1,65 @@
+//! Compiled by `intcode::transpile` from a 16 word Intcode program.
+
+#![allow(clippy::all)]
+
+use intcode::transpile::{CompiledCode, State};
+use intcode::{IntcodeError, RunStatus};
+
+const CODE: &CompiledCode = &[
+    (0, &[109, 1]),
+    (2, &[204, -1]),
+    (4, &[1001, 100, 1, 100]),
+    (8, &[1008, 100, 16, 101]),
+    (12, &[1006, 101, 0]),
+    (15, &[99]),
+];
+
+/// Runs the program until it halts or needs input, appending its outputs to `state.output`.
+pub fn run(state: &mut State) -> Result<RunStatus, IntcodeError> {
+    state.run(execute)
+}
+
+fn execute(state: &mut State, mut ip: usize) -> Result<RunStatus, IntcodeError> {
+    loop {
+        ip = match ip {
+            0 if state.matches(0, &[109, 1]) => {
+                state.relative_base = state.relative_base.saturating_add(1);
+                2
+            },
+            2 if state.matches(2, &[204, -1]) => {
+                state.output.push(state.read(state.relative(2, 204, 0, -1)?));
+                4
+            },
+            4 if state.matches(4, &[1001, 100, 1, 100]) => {
+                let value = state.add(4, 1001, state.read(100), 1)?;
+                state.store(4, 1001, 2, 100, value)?;
+                8
+            },
+            8 if state.matches(8, &[1008, 100, 16, 101]) => {
+                let value = i64::from(state.read(100) == 16);
+                state.store(8, 1008, 2, 101, value)?;
+                12
+            },
+            12 if state.matches(12, &[1006, 101, 0]) => {
+                if state.read(101) == 0 {
+                    0
+                } else {
+                    15
+                }
+            },
+            15 if state.matches(15, &[99]) => {
+                state.instruction_ptr = 15;
+                state.halted = true;
+                return Ok(RunStatus::Halted)
+            },
+            _ => {
+                state.instruction_ptr = ip;
+
+                match state.interpret(CODE)? {
+                    Some(status) => return Ok(status),
+                    None => state.instruction_ptr
+                }
+            }
+        };
+    }
+}
@@ -0,0 +1,46 @@
+//! Compiled by `intcode::transpile` from a 7 word Intcode program.
+
+#![allow(clippy::all)]
+
+use intcode::transpile::{CompiledCode, State};
+use intcode::{IntcodeError, RunStatus};
+
+const CODE: &CompiledCode = &[
+    (0, &[1101, 0, 7, 5]),
+    (4, &[104, 1]),
+    (6, &[99]),
+];
+
+/// Runs the program until it halts or needs input, appending its outputs to `state.output`.
+pub fn run(state: &mut State) -> Result<RunStatus, IntcodeError> {
+    state.run(execute)
+}
+
+fn execute(state: &mut State, mut ip: usize) -> Result<RunStatus, IntcodeError> {
+    loop {
+        ip = match ip {
+            0 if state.matches(0, &[1101, 0, 7, 5]) => {
+                let value = state.add(0, 1101, 0, 7)?;
+                state.store(0, 1101, 2, 5, value)?;
+                4
+            },
+            4 if state.matches(4, &[104, 1]) => {
+                state.output.push(1);
+                6
+            },
+            6 if state.matches(6, &[99]) => {
+                state.instruction_ptr = 6;
+                state.halted = true;
+                return Ok(RunStatus::Halted)
+            },
+            _ => {
+                state.instruction_ptr = ip;
+
+                match state.interpret(CODE)? {
+                    Some(status) => return Ok(status),
+                    None => state.instruction_ptr
+                }
+            }
+        };
+    }
+}